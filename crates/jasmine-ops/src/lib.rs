@@ -1,3 +1,5 @@
 pub mod bin;
+pub mod regression;
 
 pub use crate::bin::*;
+pub use crate::regression::*;
@@ -0,0 +1,46 @@
+use jasmine::matrix::lsq_series;
+use polars::{
+    error::{polars_bail, polars_err, PolarsResult},
+    prelude::{Column, DataType, Field, IntoColumn, IntoSeries, NamedFrom, StructChunked},
+    series::Series,
+};
+
+pub fn lsq_field() -> Field {
+    Field::new(
+        "lsq".into(),
+        DataType::Struct(vec![
+            Field::new("coef".into(), DataType::List(Box::new(DataType::Float64))),
+            Field::new(
+                "residuals".into(),
+                DataType::List(Box::new(DataType::Float64)),
+            ),
+            Field::new("r2".into(), DataType::Float64),
+        ]),
+    )
+}
+
+// y and x series, a struct of coefficients, residuals and r2 for each group
+pub fn lsq(columns: &[Column], ridge: f64, drop_nulls: bool) -> PolarsResult<Column> {
+    if columns.len() < 2 {
+        polars_bail!(InvalidOperation: "'lsq' requires y and at least one x series, got {} series", columns.len())
+    }
+    // literals are broadcast, e.g. 1 for the intercept
+    let n = columns[0].len();
+    let series: Vec<Series> = columns
+        .iter()
+        .map(|c| match c.len() {
+            1 if n != 1 => c.as_materialized_series().new_from_index(0, n),
+            _ => c.as_materialized_series().clone(),
+        })
+        .collect();
+    let out = lsq_series(&series[0], &series[1..], ridge, drop_nulls)
+        .map_err(|e| polars_err!(ComputeError: "{}", e))?;
+    let fields = [
+        Series::new("coef".into(), [Series::new("".into(), out.coef)]),
+        Series::new("residuals".into(), [Series::new("".into(), out.residuals)]),
+        Series::new("r2".into(), [out.r2]),
+    ];
+    Ok(StructChunked::from_series("lsq".into(), 1, fields.iter())?
+        .into_series()
+        .into_column())
+}
@@ -11,3 +11,30 @@ fn test_binary() {
 
     assert_eq!(result.as_series().unwrap(), &expected);
 }
+
+#[test]
+fn test_lsq() {
+    use jasmine_ops::{lsq, lsq_field};
+    use polars::prelude::*;
+
+    let y = Series::new("y".into(), &[1.0f64, 3.0, 5.0, 7.0]);
+    let one = Series::new("one".into(), &[1i64]);
+    let x = Series::new("x".into(), &[0i64, 1, 2, 3]);
+
+    let result = lsq(
+        &[y.into_column(), one.into_column(), x.into_column()],
+        0.0,
+        true,
+    )
+    .unwrap();
+    let fields = result.struct_().unwrap().fields_as_series();
+    let coef = fields[0].list().unwrap().get_as_series(0).unwrap();
+    let coef: Vec<f64> = coef.f64().unwrap().into_no_null_iter().collect();
+    assert!((coef[0] - 1.0).abs() < 1e-9 && (coef[1] - 2.0).abs() < 1e-9);
+    assert_eq!(fields[1].list().unwrap().get_as_series(0).unwrap().len(), 4);
+    assert!((fields[2].f64().unwrap().get(0).unwrap() - 1.0).abs() < 1e-12);
+    assert_eq!(result.dtype(), lsq_field().dtype());
+
+    let y = Series::new("y".into(), &[1.0f64]);
+    assert!(lsq(&[y.into_column()], 0.0, true).is_err());
+}
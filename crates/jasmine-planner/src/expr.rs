@@ -0,0 +1,98 @@
+use jasmine::{
+    errors::{JError, JResult},
+    j::J,
+};
+use polars::prelude::{binary_expr, lit, ClosedInterval, Expr, Operator, Scalar, SortOptions};
+
+// the builtin functions and operators planned natively, mirrors the ones in jasminum
+pub fn unary(name: &str, exp: Expr) -> Option<Expr> {
+    let exp = match name {
+        "abs" => exp.abs(),
+        "all" => exp.all(true),
+        "any" => exp.any(true),
+        "asc" => exp.sort(SortOptions::default()),
+        "ceil" => exp.ceil(),
+        "cmax" => exp.cum_max(false),
+        "cmin" => exp.cum_min(false),
+        "count" => exp.count(),
+        "cprod" => exp.cum_prod(false),
+        "csum" => exp.cum_sum(false),
+        "desc" => exp.sort(SortOptions::default().with_order_descending(true)),
+        "exp" => exp.exp(),
+        "first" => exp.first(),
+        "floor" => exp.floor(),
+        "isnull" => exp.is_null(),
+        "last" => exp.last(),
+        "ln" => exp.log(std::f64::consts::E),
+        "log10" => exp.log(10.0),
+        "max" => exp.max(),
+        "mean" => exp.mean(),
+        "median" => exp.median(),
+        "min" => exp.min(),
+        "neg" => -exp,
+        "not" => exp.not(),
+        "prod" => exp.product(),
+        "reverse" => exp.reverse(),
+        "sign" => exp.sign(),
+        "sqrt" => exp.sqrt(),
+        "std0" => exp.std(0),
+        "std1" => exp.std(1),
+        "sum" => exp.sum(),
+        "uc" => exp.unique_counts(),
+        "unique" => exp.unique_stable(),
+        "var0" => exp.var(0),
+        "var1" => exp.var(1),
+        _ => return None,
+    };
+    Some(exp)
+}
+
+pub fn binary(name: &str, lhs: Expr, rhs: Expr) -> Option<Expr> {
+    let op = match name {
+        "+" => Operator::Plus,
+        "-" => Operator::Minus,
+        "*" => Operator::Multiply,
+        "/" => Operator::TrueDivide,
+        "%" => Operator::Modulus,
+        "==" => Operator::Eq,
+        "!=" => Operator::NotEq,
+        "<" => Operator::Lt,
+        "<=" => Operator::LtEq,
+        ">" => Operator::Gt,
+        ">=" => Operator::GtEq,
+        "**" => return Some(lhs.pow(rhs)),
+        // '&' and '|' are min and max, clipping the lhs like jasminum does
+        "&" => return Some(lhs.clip_max(rhs)),
+        "|" => return Some(lhs.clip_min(rhs)),
+        // 'x fill y' fills nulls of y with x
+        "fill" => return Some(rhs.fill_null(lhs)),
+        "in" => return Some(lhs.is_in(rhs)),
+        "like" => return Some(lhs.str().contains(rhs, true)),
+        _ => return None,
+    };
+    Some(binary_expr(lhs, op, rhs))
+}
+
+pub fn between(exp: Expr, lower: Expr, upper: Expr) -> Expr {
+    exp.is_between(lower, upper, ClosedInterval::Both)
+}
+
+pub fn literal(j: &J) -> JResult<Expr> {
+    match j {
+        J::Series(s) => Ok(lit(s.clone())),
+        J::MixedList(_) | J::Dict(_) | J::DataFrame(_) | J::Matrix(_) | J::Err(_) => {
+            Err(JError::UnsupportedErr(format!(
+                "'{}' is not supported in sql expression",
+                j.get_type_name()
+            )))
+        }
+        _ => {
+            let s = j.into_series().map_err(JError::Err)?;
+            let value = s
+                .get(0)
+                .map_err(|e| JError::Err(e.to_string()))?
+                .into_static();
+            Ok(lit(Scalar::new(s.dtype().clone(), value)))
+        }
+    }
+}
@@ -0,0 +1,6 @@
+pub mod expr;
+pub mod planner;
+pub mod resolver;
+
+pub use planner::Planner;
+pub use resolver::Resolver;
@@ -0,0 +1,473 @@
+use std::collections::HashMap;
+
+use jasmine::{
+    errors::{JError, JResult},
+    j::{format_period, J},
+    AstNode,
+};
+use polars::prelude::{
+    all, col, concat, int_range, len, lit, ClosedWindow, DataType, Duration, DynamicGroupOptions,
+    Expr, IntoLazy, JoinArgs, JoinType, LazyFrame, PolarsError, RollingGroupOptions, Scalar,
+    SortMultipleOptions, UnionArgs, UniqueKeepStrategy,
+};
+
+use crate::{expr, resolver::Resolver};
+
+// plans jasmine sql into a lazy frame, the same way as 'eval_sql' in jasminum
+pub struct Planner<'a> {
+    resolver: &'a dyn Resolver,
+    // common table expressions, only visible to the query of 'with'
+    tables: HashMap<String, LazyFrame>,
+}
+
+fn polars_err(e: PolarsError) -> JError {
+    JError::Err(e.to_string())
+}
+
+fn fn_name(op: &AstNode) -> JResult<&str> {
+    match op {
+        AstNode::Op { name, .. } | AstNode::Id { name, .. } => Ok(name),
+        _ => Err(JError::Err(
+            "requires a builtin function name for sql expression".to_owned(),
+        )),
+    }
+}
+
+fn col_name(node: &AstNode) -> JResult<&str> {
+    match node {
+        AstNode::Id { name, .. } => Ok(name),
+        _ => Err(JError::Err("requires column name".to_owned())),
+    }
+}
+
+impl<'a> Planner<'a> {
+    pub fn new(resolver: &'a dyn Resolver) -> Self {
+        Self {
+            resolver,
+            tables: HashMap::new(),
+        }
+    }
+
+    pub fn plan(&self, node: &AstNode) -> JResult<LazyFrame> {
+        match node {
+            AstNode::Sql { .. } => self.plan_sql(node),
+            AstNode::With {
+                names, sqls, sql, ..
+            } => {
+                let mut planner = Planner {
+                    resolver: self.resolver,
+                    tables: self.tables.clone(),
+                };
+                for (name, cte) in names.iter().zip(sqls) {
+                    let lf = planner.plan(cte)?;
+                    planner.tables.insert(name.to_owned(), lf);
+                }
+                planner.plan(sql)
+            }
+            AstNode::SetOp { op, lhs, rhs, .. } => {
+                self.plan_set_op(op, self.plan_from(lhs)?, self.plan_from(rhs)?)
+            }
+            _ => Err(JError::Err("requires 'sql' or 'with' to plan".to_owned())),
+        }
+    }
+
+    // logical and optimized plans of sql, for 'explain'
+    pub fn explain(&self, node: &AstNode) -> JResult<String> {
+        let node = match node {
+            AstNode::Explain { sql, .. } => sql,
+            _ => node,
+        };
+        let lf = self.plan(node)?;
+        Ok(format!(
+            "logical plan:\n{}\n\noptimized plan:\n{}",
+            lf.describe_plan().map_err(polars_err)?,
+            lf.describe_optimized_plan().map_err(polars_err)?
+        ))
+    }
+
+    fn plan_sql(&self, node: &AstNode) -> JResult<LazyFrame> {
+        let AstNode::Sql {
+            op,
+            from,
+            filters,
+            group_type,
+            groups,
+            ops,
+            sorts,
+            take,
+            take_by,
+            skip,
+            distinct,
+            ..
+        } = node
+        else {
+            return Err(JError::Err("requires 'sql' to plan".to_owned()));
+        };
+
+        let mut lf = self.plan_from(from)?;
+        for filter in filters {
+            let filter = self.expr(filter)?;
+            lf = match op.as_str() {
+                "delete" => lf.filter(filter.not()),
+                _ => lf.filter(filter),
+            }
+        }
+
+        let ops = ops
+            .iter()
+            .map(|node| self.expr(node))
+            .collect::<JResult<Vec<_>>>()?;
+
+        if !groups.is_empty() {
+            match (op.as_str(), group_type.as_str()) {
+                ("select", "by") => {
+                    let groups = self.exprs(groups)?;
+                    lf = lf.group_by_stable(groups).agg(aggs(ops));
+                }
+                ("select", _) => {
+                    if groups.len() < 2 {
+                        return Err(JError::Err(format!(
+                            "'{}' requires 'interval' and 'column name'",
+                            group_type
+                        )));
+                    }
+                    let interval = self.interval(&groups[0])?;
+                    let index_column = self.expr(&groups[1])?;
+                    let group_by = self.exprs(&groups[2..])?;
+                    let group_by = if group_type == "dyn" {
+                        let options = DynamicGroupOptions {
+                            every: interval,
+                            period: interval,
+                            // an integer offset only applies to an integer index column
+                            offset: if interval.parsed_int {
+                                Duration::new(0)
+                            } else {
+                                Duration::parse("0ns")
+                            },
+                            ..Default::default()
+                        };
+                        lf.group_by_dynamic(index_column, group_by, options)
+                    } else {
+                        let options = RollingGroupOptions {
+                            period: interval,
+                            offset: -interval,
+                            closed_window: ClosedWindow::Right,
+                            ..Default::default()
+                        };
+                        lf.rolling(index_column, group_by, options)
+                    };
+                    lf = group_by.agg(aggs(ops));
+                }
+                ("update", "by") => {
+                    let groups = self.exprs(groups)?;
+                    let ops = ops
+                        .into_iter()
+                        .map(|op| op.over(&groups))
+                        .collect::<Vec<_>>();
+                    lf = lf.with_columns(ops);
+                }
+                _ => {
+                    return Err(JError::Err(format!(
+                        "not support '{}' with '{}'",
+                        op, group_type
+                    )))
+                }
+            }
+        } else if !ops.is_empty() {
+            lf = match op.as_str() {
+                "select" => lf.select(ops),
+                "update" => lf.with_columns(ops),
+                _ => {
+                    let names = self.names(node)?;
+                    lf.drop(names)
+                }
+            }
+        }
+
+        if *distinct {
+            lf = lf.unique_stable(None, UniqueKeepStrategy::First);
+        }
+
+        if !sorts.is_empty() {
+            let mut names = Vec::with_capacity(sorts.len());
+            let mut descending = Vec::with_capacity(sorts.len());
+            for sort in sorts {
+                let name = col_name(sort)?;
+                match name.strip_prefix('-') {
+                    Some(name) => {
+                        names.push(name.to_owned());
+                        descending.push(true);
+                    }
+                    None => {
+                        names.push(name.to_owned());
+                        descending.push(false);
+                    }
+                }
+            }
+            lf = lf.sort(
+                names,
+                SortMultipleOptions::default()
+                    .with_order_descending_multi(descending)
+                    .with_maintain_order(true),
+            );
+        }
+
+        let skip = match self.eval(skip)? {
+            J::Null => 0,
+            J::I64(n) if n >= 0 => n,
+            j => {
+                return Err(JError::Err(format!(
+                    "requires non-negative 'int' for 'skip', got {}",
+                    j.get_type_name()
+                )))
+            }
+        };
+        let take = match self.eval(take)? {
+            J::Null => None,
+            J::I64(n) => Some(n),
+            j => {
+                return Err(JError::Err(format!(
+                    "requires 'int' for 'take', got {}",
+                    j.get_type_name()
+                )))
+            }
+        };
+
+        if !take_by.is_empty() {
+            return Ok(lf.filter(take_by_filter(self.exprs(take_by)?, take, skip)));
+        }
+        if skip > 0 {
+            lf = lf.slice(skip, u32::MAX);
+        }
+        match take {
+            None => Ok(lf),
+            Some(n) if n >= 0 => Ok(lf.limit(n as u32)),
+            Some(n) => Ok(lf.tail(n.unsigned_abs() as u32)),
+        }
+    }
+
+    // columns of both sides are matched by position, and named after the left side
+    fn plan_set_op(&self, op: &str, mut lhs: LazyFrame, mut rhs: LazyFrame) -> JResult<LazyFrame> {
+        let lhs_schema = lhs.collect_schema().map_err(polars_err)?;
+        let rhs_schema = rhs.collect_schema().map_err(polars_err)?;
+        if lhs_schema.len() != rhs_schema.len() {
+            return Err(JError::Err(format!(
+                "'{}' requires the same number of columns, got {} and {}",
+                op,
+                lhs_schema.len(),
+                rhs_schema.len()
+            )));
+        }
+        let names = lhs_schema
+            .iter_names()
+            .map(|name| col(name.clone()))
+            .collect::<Vec<_>>();
+        let rhs = rhs.select(
+            rhs_schema
+                .iter_names()
+                .zip(lhs_schema.iter_names())
+                .map(|(rhs_name, lhs_name)| col(rhs_name.clone()).alias(lhs_name.clone()))
+                .collect::<Vec<_>>(),
+        );
+        let lf = match op {
+            "union all" | "union" => {
+                concat([lhs, rhs], UnionArgs::default()).map_err(polars_err)?
+            }
+            "intersect" | "except" => {
+                let mut args = JoinArgs::new(if op == "intersect" {
+                    JoinType::Semi
+                } else {
+                    JoinType::Anti
+                });
+                args.join_nulls = true;
+                lhs.join(rhs, &names, &names, args)
+            }
+            _ => return Err(JError::Err(format!("unknown set operator '{}'", op))),
+        };
+        // set operators except 'union all' return distinct rows
+        if op == "union all" {
+            Ok(lf)
+        } else {
+            Ok(lf.unique_stable(None, UniqueKeepStrategy::First))
+        }
+    }
+
+    fn plan_from(&self, from: &AstNode) -> JResult<LazyFrame> {
+        match from {
+            AstNode::Sql { .. } | AstNode::With { .. } | AstNode::SetOp { .. } => self.plan(from),
+            AstNode::Id { name, .. } if self.tables.contains_key(name) => {
+                Ok(self.tables[name].clone())
+            }
+            _ => match self.eval(from)? {
+                J::DataFrame(df) => Ok(df.lazy()),
+                // files are scanned by jasminum
+                J::String(_) | J::Cat(_) => Err(JError::UnsupportedErr(
+                    "'from' a file is not supported by planner".to_owned(),
+                )),
+                j => Err(JError::Err(format!(
+                    "'from' requires 'dataframe', got {}",
+                    j.get_type_name()
+                ))),
+            },
+        }
+    }
+
+    // names of deleted columns
+    fn names(&self, node: &AstNode) -> JResult<Vec<String>> {
+        let AstNode::Sql { ops, .. } = node else {
+            return Ok(Vec::new());
+        };
+        ops.iter()
+            .map(|op| col_name(op).map(|name| name.to_owned()))
+            .collect()
+    }
+
+    // evaluates a variable or a literal
+    fn eval(&self, node: &AstNode) -> JResult<J> {
+        match node {
+            AstNode::J(j) => Ok(j.clone()),
+            AstNode::Id { name, .. } => match self.resolver.resolve(name)? {
+                Some(j) => Ok(j),
+                None => Err(JError::Err(format!("'{}' is not defined", name))),
+            },
+            // parameters are resolved as '$name'
+            AstNode::Param { name, .. } => match self.resolver.resolve(&format!("${}", name))? {
+                Some(j) => Ok(j),
+                None => Err(JError::Err(format!("parameter '${}' is not bound", name))),
+            },
+            _ => Err(JError::UnsupportedErr(
+                "requires a variable or a literal, got an expression".to_owned(),
+            )),
+        }
+    }
+
+    fn interval(&self, node: &AstNode) -> JResult<Duration> {
+        let interval = match self.eval(node)? {
+            J::I64(n) => format!("{}i", n),
+            J::Duration(ns) => format!("{}ns", ns),
+            J::Period { months, days, ns } => format_period(months, days, ns),
+            J::String(s) => s,
+            j => {
+                return Err(JError::Err(format!(
+                    "requires 'int|duration|period' for interval, got {}",
+                    j.get_type_name()
+                )))
+            }
+        };
+        Duration::try_parse(&interval).map_err(polars_err)
+    }
+
+    fn exprs(&self, nodes: &[AstNode]) -> JResult<Vec<Expr>> {
+        nodes.iter().map(|node| self.expr(node)).collect()
+    }
+
+    pub fn expr(&self, node: &AstNode) -> JResult<Expr> {
+        match node {
+            AstNode::J(j) => expr::literal(j),
+            AstNode::Id { name, .. } => match self.resolver.resolve(name)? {
+                Some(j) => expr::literal(&j),
+                None if name == "i" => Ok(int_range(lit(0), len(), 1, DataType::UInt32).alias("i")),
+                None if !name.contains('.') => Ok(col(name.as_str())),
+                None => Err(JError::Err(format!("'{}' is not defined", name))),
+            },
+            AstNode::Param { .. } => expr::literal(&self.eval(node)?),
+            AstNode::Series { name, exp } => Ok(self.expr(exp)?.alias(name.as_str())),
+            AstNode::UnaryOp { op, exp } => self.call(fn_name(op)?, &[exp.as_ref()]),
+            AstNode::BinOp { op, lhs, rhs } => {
+                self.call(fn_name(op)?, &[lhs.as_ref(), rhs.as_ref()])
+            }
+            AstNode::Call { f, args, .. } => {
+                self.call(fn_name(f)?, &args.iter().collect::<Vec<_>>())
+            }
+            AstNode::SubQuery { kind, exp, sql, .. } => {
+                let df = self.plan(sql)?.collect().map_err(polars_err)?;
+                let column = df
+                    .get_columns()
+                    .first()
+                    .ok_or(JError::Err("subquery requires 1 column, got 0".to_owned()))?
+                    .as_materialized_series()
+                    .clone();
+                match kind.as_str() {
+                    "scalar" => {
+                        if df.shape() != (1, 1) {
+                            return Err(JError::Err(format!(
+                                "scalar subquery requires 1 row, got {}",
+                                df.height()
+                            )));
+                        }
+                        let value = column.get(0).map_err(polars_err)?.into_static();
+                        Ok(lit(Scalar::new(column.dtype().clone(), value)))
+                    }
+                    "in" => Ok(self.expr(exp)?.is_in(lit(column))),
+                    _ => Ok(self.expr(exp)?.is_in(lit(column)).not()),
+                }
+            }
+            _ => Err(JError::UnsupportedErr(
+                "not support this expression in sql, requires series, literal or function call"
+                    .to_owned(),
+            )),
+        }
+    }
+
+    fn call(&self, name: &str, args: &[&AstNode]) -> JResult<Expr> {
+        let exp = match args {
+            [arg] => expr::unary(name, self.expr(arg)?),
+            [exp, AstNode::List(bounds)] if name == "between" && bounds.len() == 2 => {
+                Some(expr::between(
+                    self.expr(exp)?,
+                    self.expr(&bounds[0])?,
+                    self.expr(&bounds[1])?,
+                ))
+            }
+            [exp, bounds] if name == "between" => match self.eval(bounds)? {
+                J::Series(s) if s.len() == 2 => {
+                    let lower = s.get(0).map_err(polars_err)?.into_static();
+                    let upper = s.get(1).map_err(polars_err)?.into_static();
+                    Some(expr::between(
+                        self.expr(exp)?,
+                        lit(Scalar::new(s.dtype().clone(), lower)),
+                        lit(Scalar::new(s.dtype().clone(), upper)),
+                    ))
+                }
+                j => {
+                    return Err(JError::Err(format!(
+                        "'between' requires 2 bounds, got {}",
+                        j.get_type_name()
+                    )))
+                }
+            },
+            [lhs, rhs] => expr::binary(name, self.expr(lhs)?, self.expr(rhs)?),
+            _ => None,
+        };
+        exp.ok_or(JError::UnsupportedErr(format!(
+            "'{}' with {} argument(s) is not supported by planner",
+            name,
+            args.len()
+        )))
+    }
+}
+
+// keeps rows of each group after skipping the first 'skip' rows, the first n rows if n is
+// positive, otherwise the last n rows
+fn take_by_filter(by: Vec<Expr>, take: Option<i64>, skip: i64) -> Expr {
+    let index = int_range(lit(0), len(), 1, DataType::Int64);
+    let mut filter = index.clone().over(&by).gt_eq(lit(skip));
+    match take {
+        Some(n) if n >= 0 => filter = filter.and(index.over(&by).lt(lit(skip + n))),
+        Some(n) => {
+            let reversed = len().cast(DataType::Int64) - lit(1i64) - index;
+            filter = filter.and(reversed.over(&by).lt(lit(-n)))
+        }
+        None => {}
+    }
+    filter
+}
+
+// takes the last row of each group if no aggregation, same as 'select by'
+fn aggs(ops: Vec<Expr>) -> Vec<Expr> {
+    if ops.is_empty() {
+        vec![all().last()]
+    } else {
+        ops
+    }
+}
@@ -0,0 +1,14 @@
+use std::collections::HashMap;
+
+use jasmine::{errors::JResult, j::J};
+
+// resolves variables referred by sql, names not resolved are treated as columns
+pub trait Resolver {
+    fn resolve(&self, name: &str) -> JResult<Option<J>>;
+}
+
+impl Resolver for HashMap<String, J> {
+    fn resolve(&self, name: &str) -> JResult<Option<J>> {
+        Ok(self.get(name).cloned())
+    }
+}
@@ -0,0 +1,317 @@
+use std::collections::HashMap;
+
+use jasmine::{errors::JError, j::J, parse};
+use jasmine_planner::Planner;
+use polars::{
+    df,
+    prelude::{DataFrame, NamedFrom},
+    series::Series,
+};
+
+fn query(code: &str, vars: &HashMap<String, J>) -> DataFrame {
+    let nodes = parse(code, 0).unwrap();
+    Planner::new(vars)
+        .plan(&nodes[0])
+        .unwrap()
+        .collect()
+        .unwrap()
+}
+
+fn vars() -> HashMap<String, J> {
+    let t = df![
+        "sym" => ["a", "b", "a", "c", "b"],
+        "qty" => [1i64, 2, 3, 4, 5],
+        "price" => [1.0, 2.0, 3.0, 4.0, 5.0],
+    ]
+    .unwrap();
+    HashMap::from([
+        ("t".to_owned(), J::DataFrame(t)),
+        ("threshold".to_owned(), J::I64(2)),
+    ])
+}
+
+#[test]
+fn plan_select() {
+    let vars = vars();
+    let df = query("select sym, qty from t where qty > threshold", &vars);
+    assert_eq!(
+        df,
+        df!["sym" => ["a", "c", "b"], "qty" => [3i64, 4, 5]].unwrap()
+    );
+
+    let df = query("select total = sum qty by sym from t", &vars);
+    assert_eq!(
+        df,
+        df!["sym" => ["a", "b", "c"], "total" => [4i64, 7, 4]].unwrap()
+    );
+
+    let df = query("select from t sort -qty take 2", &vars);
+    assert_eq!(
+        df.column("qty").unwrap().i64().unwrap().to_vec(),
+        [Some(5), Some(4)]
+    );
+
+    let df = query(
+        "select sym from t where sym in (select sym from t where qty > 4)",
+        &vars,
+    );
+    assert_eq!(df, df!["sym" => ["b", "b"]].unwrap());
+
+    let df = query("select s = sum qty dyn 2, qty from t", &vars);
+    assert_eq!(df, df!["qty" => [0i64, 2, 4], "s" => [1i64, 5, 9]].unwrap());
+
+    let mut vars = vars;
+    let days = parse(
+        "df[date=2024-01-01 2024-01-15 2024-02-01 2024-03-10, v=1 2 3 4]",
+        0,
+    )
+    .unwrap()
+    .pop()
+    .unwrap()
+    .as_j()
+    .unwrap();
+    vars.insert("days".to_owned(), days);
+    let df = query("select s = sum v dyn 1mo, date from days", &vars);
+    assert_eq!(
+        df.column("s").unwrap().i64().unwrap().to_vec(),
+        [Some(3), Some(3), Some(4)]
+    );
+    let df = query("select s = sum v dyn 2w, date from days", &vars);
+    assert_eq!(df.height(), 4);
+
+    // periods of variables, jasminum resolves them as duration strings
+    vars.insert(
+        "p".to_owned(),
+        J::Period {
+            months: 1,
+            days: 0,
+            ns: 0,
+        },
+    );
+    vars.insert("p_str".to_owned(), J::String("1mo".to_owned()));
+    for code in [
+        "select s = sum v dyn p, date from days",
+        "select s = sum v dyn p_str, date from days",
+    ] {
+        let df = query(code, &vars);
+        assert_eq!(
+            df.column("s").unwrap().i64().unwrap().to_vec(),
+            [Some(3), Some(3), Some(4)]
+        );
+    }
+
+    let df = query(
+        "select v from days where date >= 2024W03, date < 2024Q2",
+        &vars,
+    );
+    assert_eq!(df, df!["v" => [2i64, 3, 4]].unwrap());
+    let df = query("select v from days where date == 2024-02m", &vars);
+    assert_eq!(df, df!["v" => [3i64]].unwrap());
+
+    // months of variables, jasminum resolves them as their first days
+    vars.insert("m".to_owned(), J::Month((2024 - 1970) * 12 + 1));
+    vars.insert("m_date".to_owned(), J::Date(19754));
+    for code in [
+        "select v from days where date >= m",
+        "select v from days where date >= m_date",
+    ] {
+        assert_eq!(query(code, &vars), df!["v" => [3i64, 4]].unwrap());
+    }
+
+    let months = parse("df[month=2024-01m 2024-01m 2024Q1 2024-03m, v=1 2 3 4]", 0)
+        .unwrap()
+        .pop()
+        .unwrap()
+        .as_j()
+        .unwrap();
+    vars.insert("months".to_owned(), months);
+    let df = query("select s = sum v dyn 1mo, month from months", &vars);
+    assert_eq!(
+        df.column("s").unwrap().i64().unwrap().to_vec(),
+        [Some(6), Some(4)]
+    );
+}
+
+#[test]
+fn plan_distinct_take_skip() {
+    let vars = vars();
+    let df = query("select distinct sym from t", &vars);
+    assert_eq!(df, df!["sym" => ["a", "b", "c"]].unwrap());
+
+    let df = query("select qty from t take 2 skip 1", &vars);
+    assert_eq!(df, df!["qty" => [2i64, 3]].unwrap());
+
+    let df = query("select qty from t take -2 skip 1", &vars);
+    assert_eq!(df, df!["qty" => [4i64, 5]].unwrap());
+
+    let df = query("select sym, qty from t take 1 by sym", &vars);
+    assert_eq!(
+        df,
+        df!["sym" => ["a", "b", "c"], "qty" => [1i64, 2, 4]].unwrap()
+    );
+
+    let df = query("select sym, qty from t take -1 by sym", &vars);
+    assert_eq!(
+        df,
+        df!["sym" => ["a", "c", "b"], "qty" => [3i64, 4, 5]].unwrap()
+    );
+
+    let df = query("select sym, qty from t take 1 by sym skip 1", &vars);
+    assert_eq!(df, df!["sym" => ["a", "b"], "qty" => [3i64, 5]].unwrap());
+}
+
+#[test]
+fn plan_update_delete() {
+    let vars = vars();
+    let df = query("update qty = qty * 10 from t where sym == `a", &vars);
+    assert_eq!(
+        df.column("qty").unwrap().as_materialized_series(),
+        &Series::new("qty".into(), [10i64, 30])
+    );
+
+    let df = query("delete qty, price from t", &vars);
+    assert_eq!(df.get_column_names(), ["sym"]);
+
+    let df = query("delete from t where qty < 4", &vars);
+    assert_eq!(df.height(), 2);
+}
+
+#[test]
+fn plan_set_op() {
+    let vars = vars();
+    let df = query(
+        "select sym from t where qty < 3 union all select sym from t where qty > 3",
+        &vars,
+    );
+    assert_eq!(df, df!["sym" => ["a", "b", "c", "b"]].unwrap());
+
+    let df = query(
+        "select sym from t where qty < 3 union select s = sym from t where qty > 3",
+        &vars,
+    );
+    assert_eq!(df, df!["sym" => ["a", "b", "c"]].unwrap());
+
+    let df = query(
+        "select sym from t where qty < 3 intersect select sym from t where qty > 3",
+        &vars,
+    );
+    assert_eq!(df, df!["sym" => ["b"]].unwrap());
+
+    let df = query("select qty from t except df[qty=2 3] union df[n=9]", &vars);
+    assert_eq!(df, df!["qty" => [1i64, 4, 5, 9]].unwrap());
+
+    let nodes = parse("select sym from t union select sym, qty from t", 0).unwrap();
+    assert_eq!(
+        Planner::new(&vars)
+            .plan(&nodes[0])
+            .err()
+            .unwrap()
+            .to_string(),
+        "'union' requires the same number of columns, got 1 and 2"
+    );
+}
+
+#[test]
+fn plan_param() {
+    let mut vars = vars();
+    vars.insert("$1".to_owned(), J::I64(3));
+    let df = query("select qty from t where qty > $1 take $1", &vars);
+    assert_eq!(df, df!["qty" => [4i64, 5]].unwrap());
+
+    let nodes = parse("select from t where qty > $min", 0).unwrap();
+    assert_eq!(
+        Planner::new(&vars)
+            .plan(&nodes[0])
+            .err()
+            .unwrap()
+            .to_string(),
+        "parameter '$min' is not bound"
+    );
+}
+
+#[test]
+fn plan_with() {
+    let vars = vars();
+    let df = query(
+        "with a as (select from t where qty > 1), b as (select n = count i by sym from a) select from b sort sym",
+        &vars,
+    );
+    assert_eq!(
+        df,
+        df!["sym" => ["a", "b", "c"], "n" => [1u32, 2, 1]].unwrap()
+    );
+}
+
+#[test]
+fn plan_unsupported() {
+    let vars = vars();
+    let nodes = parse("select from undefined_table", 0).unwrap();
+    assert_eq!(
+        Planner::new(&vars)
+            .plan(&nodes[0])
+            .err()
+            .unwrap()
+            .to_string(),
+        "'undefined_table' is not defined"
+    );
+
+    // jasminum evaluates queries the planner doesn't support
+    for code in [
+        "select from \"trades.csv\"",
+        "select unknown_fn(qty, price, sym) from t",
+        "select from t where qty > {a: 1}",
+    ] {
+        let nodes = parse(code, 0).unwrap();
+        assert!(
+            matches!(
+                Planner::new(&vars).plan(&nodes[0]),
+                Err(JError::UnsupportedErr(_))
+            ),
+            "{}",
+            code
+        );
+    }
+    let nodes = parse("select from threshold", 0).unwrap();
+    assert!(matches!(
+        Planner::new(&vars).plan(&nodes[0]),
+        Err(JError::Err(_))
+    ));
+}
+
+#[test]
+fn plan_explain() {
+    let vars = vars();
+    let nodes = parse("explain select sum qty by sym from t where qty > 1", 0).unwrap();
+    let plans = Planner::new(&vars).explain(&nodes[0]).unwrap();
+    assert!(plans.starts_with("logical plan:\n"));
+    assert!(plans.contains("\n\noptimized plan:\n"));
+    assert!(plans.contains("AGGREGATE"));
+}
+
+// nulls propagate the same way as the builtins in jasminum, '&' and '|' don't clip with null bounds
+#[test]
+fn plan_builtin_nulls() {
+    let nulls = df![
+        "s" => [Some("ab"), None, Some("cb")],
+        "v" => [Some(1i64), None, Some(5)],
+        "w" => [Some(2i64), Some(3), None],
+    ]
+    .unwrap();
+    let vars = HashMap::from([("n".to_owned(), J::DataFrame(nulls))]);
+    let df = query(
+        "select f = 0 ~fill v, mn = v & w, mx = v | w, l = s ~like \"^a\", i = v ~in 1 3, b = v ~between 1 3 from n",
+        &vars,
+    );
+    assert_eq!(
+        df,
+        df![
+            "f" => [1i64, 0, 5],
+            "mn" => [Some(1i64), None, Some(5)],
+            "mx" => [Some(2i64), None, Some(5)],
+            "l" => [Some(true), None, Some(false)],
+            "i" => [Some(true), None, Some(false)],
+            "b" => [Some(true), None, Some(false)],
+        ]
+        .unwrap()
+    );
+}
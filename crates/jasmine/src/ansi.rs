@@ -0,0 +1,712 @@
+use crate::ast_node::AstNode;
+use crate::j::J;
+use crate::parser::{
+    check_single_column, fold_set_ops, parse_date, parse_timestamp, ParseOptions, UNIX_EPOCH_DAY,
+};
+use pest::error::{Error as PestError, ErrorVariant};
+use pest::iterators::Pair;
+use pest::{Parser, Span};
+use pest_derive::Parser;
+
+#[derive(Parser)]
+#[grammar = "ansi.pest"]
+pub struct AnsiParser;
+
+type AnsiResult<T> = Result<T, PestError<Rule>>;
+
+// tables and aliases of the current select, to resolve qualified column names
+struct Scope<'a> {
+    source_id: usize,
+    // of timestamps without a timezone
+    timezone: &'a str,
+    tables: Vec<String>,
+}
+
+impl Scope<'_> {
+    fn id(&self, name: &str, start: usize) -> AstNode {
+        AstNode::Id {
+            name: name.to_owned(),
+            start,
+            source_id: self.source_id,
+        }
+    }
+
+    fn bin_op(&self, op: &str, start: usize, lhs: AstNode, rhs: AstNode) -> AstNode {
+        AstNode::BinOp {
+            op: Box::new(AstNode::Op {
+                name: op.to_owned(),
+                start,
+                source_id: self.source_id,
+            }),
+            lhs: Box::new(lhs),
+            rhs: Box::new(rhs),
+        }
+    }
+
+    fn unary_op(&self, op: &str, start: usize, exp: AstNode) -> AstNode {
+        AstNode::UnaryOp {
+            op: Box::new(self.id(op, start)),
+            exp: Box::new(exp),
+        }
+    }
+}
+
+pub fn parse_ansi_sql(source: &str, source_id: usize) -> AnsiResult<AstNode> {
+    parse_ansi_sql_with_options(source, &ParseOptions::local(source_id))
+}
+
+pub fn parse_ansi_sql_with_options(source: &str, opts: &ParseOptions) -> AnsiResult<AstNode> {
+    let source_id = opts.source_id;
+    let pairs = AnsiParser::parse(Rule::Query, source)?;
+    let mut sqls: Vec<(usize, AstNode)> = Vec::new();
+    let mut ops: Vec<String> = Vec::new();
+    for pair in pairs.into_iter().next().unwrap().into_inner() {
+        match pair.as_rule() {
+            Rule::SelectStmt => {
+                let start = pair.as_span().start();
+                sqls.push((start, lower_select(pair, source_id, &opts.timezone)?))
+            }
+            Rule::SetOperator => ops.push(normalize(pair.as_str()).to_lowercase()),
+            _ => {}
+        }
+    }
+    Ok(fold_set_ops(sqls, ops, source_id))
+}
+
+fn lower_select(pair: Pair<Rule>, source_id: usize, timezone: &str) -> AnsiResult<AstNode> {
+    let span = pair.as_span();
+    let mut scope = Scope {
+        source_id,
+        timezone,
+        tables: Vec::new(),
+    };
+    let mut select_list = None;
+    let mut from = None;
+    let mut filters: Vec<AstNode> = Vec::new();
+    let mut group_pairs = Vec::new();
+    let mut order_pairs = Vec::new();
+    let mut take = AstNode::J(J::Null);
+    let mut skip = AstNode::J(J::Null);
+    let mut distinct = false;
+    for some_pair in pair.into_inner() {
+        match some_pair.as_rule() {
+            Rule::SELECT | Rule::ALL => {}
+            Rule::DISTINCT => distinct = true,
+            Rule::SelectList => select_list = Some(some_pair),
+            Rule::FromClause => {
+                let mut tables = some_pair
+                    .into_inner()
+                    .filter(|p| p.as_rule() == Rule::TableRef);
+                from = Some(lower_table(tables.next().unwrap(), &mut scope)?);
+                if let Some(table) = tables.next() {
+                    return Err(raise_error(
+                        "multiple tables in 'FROM' are not supported".to_owned(),
+                        table.as_span(),
+                    ));
+                }
+            }
+            Rule::JoinClause => {
+                return Err(raise_error(
+                    "'JOIN' is not supported".to_owned(),
+                    some_pair.as_span(),
+                ))
+            }
+            Rule::WhereClause => {
+                let exp = some_pair.into_inner().nth(1).unwrap();
+                // top level conjunctions become separate filters
+                let mut and_exps = exp.clone().into_inner();
+                if and_exps.len() == 1 {
+                    for not_exp in and_exps.next().unwrap().into_inner() {
+                        if not_exp.as_rule() == Rule::NotExp {
+                            filters.push(lower_exp(not_exp, &scope)?)
+                        }
+                    }
+                } else {
+                    filters.push(lower_exp(exp, &scope)?)
+                }
+            }
+            Rule::GroupByClause => {
+                group_pairs = some_pair
+                    .into_inner()
+                    .filter(|p| p.as_rule() == Rule::Expr)
+                    .collect()
+            }
+            Rule::HavingClause => {
+                return Err(raise_error(
+                    "'HAVING' is not supported, filter the grouped result in an outer query"
+                        .to_owned(),
+                    some_pair.as_span(),
+                ))
+            }
+            Rule::OrderByClause => {
+                order_pairs = some_pair
+                    .into_inner()
+                    .filter(|p| p.as_rule() == Rule::OrderItem)
+                    .collect()
+            }
+            Rule::LimitClause => {
+                let limit = some_pair.into_inner().nth(1).unwrap();
+                if limit.as_rule() == Rule::Integer {
+                    take = AstNode::J(lower_integer(&limit)?)
+                }
+            }
+            Rule::OffsetClause => {
+                skip = AstNode::J(lower_integer(&some_pair.into_inner().nth(1).unwrap())?)
+            }
+            unexpected_exp => {
+                return Err(raise_error(
+                    format!("Unexpected sql: {:?}", unexpected_exp),
+                    some_pair.as_span(),
+                ))
+            }
+        }
+    }
+
+    let from = match from {
+        Some(from) => from,
+        None => return Err(raise_error("'FROM' is required".to_owned(), span)),
+    };
+
+    // (name, exp), name is the output column name if known
+    let mut items: Vec<(Option<String>, AstNode)> = Vec::new();
+    // source text of items to match 'GROUP BY' expressions, regardless of their positions
+    let mut item_sources: Vec<String> = Vec::new();
+    let item_pairs: Vec<Pair<Rule>> = select_list.unwrap().into_inner().collect();
+    let item_num = item_pairs.len();
+    for item_pair in item_pairs {
+        let item_span = item_pair.as_span();
+        let mut pairs = item_pair.into_inner();
+        let exp_pair = pairs.next().unwrap();
+        if exp_pair.as_rule() == Rule::Star {
+            if item_num > 1 {
+                return Err(raise_error(
+                    "'*' cannot be combined with other columns".to_owned(),
+                    item_span,
+                ));
+            }
+            continue;
+        }
+        let is_count_all = exp_pair.as_str().replace(' ', "").to_lowercase() == "count(*)";
+        item_sources.push(source_text(&exp_pair));
+        let exp = lower_exp(exp_pair, &scope)?;
+        let alias = pairs
+            .find(|p| p.as_rule() != Rule::AS)
+            .map(|p| unquote(p.as_str()));
+        match alias {
+            Some(alias) => items.push((
+                Some(alias.clone()),
+                AstNode::Series {
+                    name: alias,
+                    exp: Box::new(exp),
+                },
+            )),
+            None if is_count_all => items.push((
+                Some("count".to_owned()),
+                AstNode::Series {
+                    name: "count".to_owned(),
+                    exp: Box::new(exp),
+                },
+            )),
+            None => {
+                let name = if let AstNode::Id { name, .. } = &exp {
+                    Some(name.to_owned())
+                } else {
+                    None
+                };
+                items.push((name, exp))
+            }
+        }
+    }
+
+    let mut groups = Vec::with_capacity(group_pairs.len());
+    let mut group_sources = Vec::with_capacity(group_pairs.len());
+    for group_pair in group_pairs {
+        match position(&group_pair, items.len())? {
+            Some(i) => {
+                groups.push(items[i].1.clone());
+                group_sources.push(item_sources[i].clone());
+            }
+            None => {
+                group_sources.push(source_text(&group_pair));
+                groups.push(lower_exp(group_pair, &scope)?);
+            }
+        }
+    }
+
+    let mut sorts = Vec::with_capacity(order_pairs.len());
+    for order_pair in order_pairs {
+        let mut pairs = order_pair.into_inner();
+        let exp_pair = pairs.next().unwrap();
+        let exp_span = exp_pair.as_span();
+        let name = match position(&exp_pair, items.len())? {
+            Some(i) => items[i].0.clone(),
+            None => match lower_exp(exp_pair, &scope)? {
+                AstNode::Id { name, .. } => Some(name),
+                _ => None,
+            },
+        };
+        let name = match name {
+            Some(name) => name,
+            None => {
+                return Err(raise_error(
+                    "'ORDER BY' requires a column name or position".to_owned(),
+                    exp_span,
+                ))
+            }
+        };
+        let mut descending = false;
+        for p in pairs {
+            match p.as_rule() {
+                Rule::DESC => descending = true,
+                Rule::NULLS => {
+                    return Err(raise_error(
+                        "'NULLS FIRST|LAST' is not supported".to_owned(),
+                        p.as_span(),
+                    ))
+                }
+                _ => {}
+            }
+        }
+        sorts.push(AstNode::Id {
+            name: if descending {
+                format!("-{}", name)
+            } else {
+                name
+            },
+            start: exp_span.start(),
+            source_id,
+        })
+    }
+
+    // group keys are part of the result, only aggregations are kept as ops
+    let mut ops = Vec::with_capacity(items.len());
+    let group_names: Vec<&str> = groups
+        .iter()
+        .filter_map(|g| match g {
+            AstNode::Id { name, .. } => Some(name.as_str()),
+            _ => None,
+        })
+        .collect();
+    for ((_, exp), source) in items.iter().zip(item_sources.iter()) {
+        if group_sources.contains(source) {
+            continue;
+        }
+        if let AstNode::Id { name, start, .. } = exp {
+            if group_names.contains(&name.as_str()) {
+                continue;
+            } else if !groups.is_empty() {
+                let span = Span::new(span.get_input(), *start, start + name.len()).unwrap_or(span);
+                return Err(raise_error(
+                    format!(
+                        "column '{}' must appear in 'GROUP BY' or be used in an aggregate function",
+                        name
+                    ),
+                    span,
+                ));
+            }
+        }
+        ops.push(exp.clone())
+    }
+
+    Ok(AstNode::Sql {
+        op: "select".to_owned(),
+        from: Box::new(from),
+        filters,
+        group_type: "by".to_owned(),
+        groups,
+        ops,
+        sorts,
+        take: Box::new(take),
+        take_by: Vec::new(),
+        skip: Box::new(skip),
+        distinct,
+        source_id,
+        start: span.start(),
+    })
+}
+
+fn lower_table(pair: Pair<Rule>, scope: &mut Scope) -> AnsiResult<AstNode> {
+    let mut pairs = pair.into_inner();
+    let table = pairs.next().unwrap();
+    let node = if table.as_rule() == Rule::SubQuery {
+        lower_select(
+            table.into_inner().next().unwrap(),
+            scope.source_id,
+            scope.timezone,
+        )?
+    } else {
+        let name = unquote(table.as_str());
+        scope.tables.push(name.clone());
+        scope.id(&name, table.as_span().start())
+    };
+    if let Some(alias) = pairs.find(|p| p.as_rule() != Rule::AS) {
+        scope.tables.push(unquote(alias.as_str()))
+    }
+    Ok(node)
+}
+
+fn lower_exp(pair: Pair<Rule>, scope: &Scope) -> AnsiResult<AstNode> {
+    let span = pair.as_span();
+    match pair.as_rule() {
+        Rule::Expr | Rule::AndExp | Rule::Additive | Rule::Multiplicative => {
+            let mut pairs = pair.into_inner();
+            let mut lhs = lower_exp(pairs.next().unwrap(), scope)?;
+            while let Some(op) = pairs.next() {
+                let name = match op.as_rule() {
+                    Rule::OR => "|",
+                    Rule::AND => "&",
+                    _ => match op.as_str() {
+                        "||" => return Err(unsupported(&op)),
+                        op => op,
+                    },
+                };
+                let rhs = lower_exp(pairs.next().unwrap(), scope)?;
+                lhs = scope.bin_op(name, op.as_span().start(), lhs, rhs)
+            }
+            Ok(lhs)
+        }
+        Rule::NotExp => {
+            let pairs: Vec<Pair<Rule>> = pair.into_inner().collect();
+            let exp = lower_exp(pairs.last().unwrap().clone(), scope)?;
+            if (pairs.len() - 1) % 2 == 1 {
+                Ok(scope.unary_op("not", span.start(), exp))
+            } else {
+                Ok(exp)
+            }
+        }
+        Rule::Predicate => {
+            let mut pairs = pair.into_inner();
+            let lhs = lower_exp(pairs.next().unwrap(), scope)?;
+            match pairs.next() {
+                Some(tail) => lower_predicate(tail, lhs, scope),
+                None => Ok(lhs),
+            }
+        }
+        Rule::UnaryExp => {
+            let pairs: Vec<Pair<Rule>> = pair.into_inner().collect();
+            let exp = lower_exp(pairs.last().unwrap().clone(), scope)?;
+            if pairs.len() % 2 == 1 {
+                return Ok(exp);
+            }
+            match exp {
+                AstNode::J(J::I64(n)) => Ok(AstNode::J(J::I64(-n))),
+                AstNode::J(J::F64(n)) => Ok(AstNode::J(J::F64(-n))),
+                exp => Ok(scope.unary_op("neg", span.start(), exp)),
+            }
+        }
+        Rule::Integer => Ok(AstNode::J(lower_integer(&pair)?)),
+        Rule::Decimal => match pair.as_str().parse::<f64>() {
+            Ok(n) => Ok(AstNode::J(J::F64(n))),
+            Err(e) => Err(raise_error(e.to_string(), span)),
+        },
+        Rule::String => Ok(AstNode::J(J::String(unquote_string(pair.as_str())))),
+        Rule::NULL => Ok(AstNode::J(J::Null)),
+        Rule::TRUE => Ok(AstNode::J(J::Boolean(true))),
+        Rule::FALSE => Ok(AstNode::J(J::Boolean(false))),
+        Rule::DateLiteral => {
+            let s = unquote_string(pair.into_inner().nth(1).unwrap().as_str());
+            parse_date(&s)
+                .map(|d| AstNode::J(J::Date(d - UNIX_EPOCH_DAY)))
+                .map_err(|e| raise_error(e, span))
+        }
+        Rule::TimestampLiteral => {
+            let s = unquote_string(pair.into_inner().nth(1).unwrap().as_str());
+            // jasmine timestamps use 'D' to separate date and time
+            let ts = if s.len() > 10 {
+                s.replacen([' ', 'T'], "D", 1)
+            } else {
+                format!("{}D", s)
+            };
+            parse_timestamp(&ts, scope.timezone)
+                .map(|(ns, timezone)| AstNode::J(J::Timestamp { ns, timezone }))
+                .map_err(|e| raise_error(e, span))
+        }
+        Rule::ColumnRef => {
+            let names: Vec<Pair<Rule>> = pair.into_inner().collect();
+            if names.len() == 2 {
+                let table = unquote(names[0].as_str());
+                if !scope.tables.contains(&table) {
+                    return Err(raise_error(
+                        format!("unknown table '{}'", table),
+                        names[0].as_span(),
+                    ));
+                }
+            }
+            let name = names.last().unwrap();
+            Ok(scope.id(&unquote(name.as_str()), name.as_span().start()))
+        }
+        Rule::Function => lower_function(pair, scope),
+        Rule::SubQuery => {
+            let sql = lower_select(
+                pair.into_inner().next().unwrap(),
+                scope.source_id,
+                scope.timezone,
+            )?;
+            check_single_column(&sql).map_err(|e| raise_error(e, span))?;
+            Ok(AstNode::SubQuery {
+                kind: "scalar".to_owned(),
+                exp: Box::new(AstNode::Skip),
+                sql: Box::new(sql),
+                start: span.start(),
+                source_id: scope.source_id,
+            })
+        }
+        Rule::CaseExp => Err(raise_error("'CASE' is not supported".to_owned(), span)),
+        unexpected_exp => Err(raise_error(
+            format!("Unexpected rule: {:?}", unexpected_exp),
+            span,
+        )),
+    }
+}
+
+fn lower_predicate(pair: Pair<Rule>, lhs: AstNode, scope: &Scope) -> AnsiResult<AstNode> {
+    let span = pair.as_span();
+    let rule = pair.as_rule();
+    let pairs: Vec<Pair<Rule>> = pair.into_inner().collect();
+    let is_not = pairs.iter().any(|p| p.as_rule() == Rule::NOT);
+    let operands: Vec<Pair<Rule>> = pairs
+        .into_iter()
+        .filter(|p| {
+            !matches!(
+                p.as_rule(),
+                Rule::NOT | Rule::IN | Rule::BETWEEN | Rule::AND | Rule::LIKE | Rule::IS
+            )
+        })
+        .collect();
+    let start = span.start();
+    let exp = match rule {
+        Rule::Comparison => {
+            let op = match operands[0].as_str() {
+                "=" => "==",
+                "<>" => "!=",
+                op => op,
+            };
+            let rhs = lower_exp(operands[1].clone(), scope)?;
+            scope.bin_op(op, start, lhs, rhs)
+        }
+        Rule::InList => {
+            if operands[0].as_rule() == Rule::SelectStmt {
+                let sql = lower_select(operands[0].clone(), scope.source_id, scope.timezone)?;
+                check_single_column(&sql).map_err(|e| raise_error(e, span))?;
+                return Ok(AstNode::SubQuery {
+                    kind: if is_not { "not in" } else { "in" }.to_owned(),
+                    exp: Box::new(lhs),
+                    sql: Box::new(sql),
+                    start,
+                    source_id: scope.source_id,
+                });
+            }
+            let mut series = None;
+            for operand in operands {
+                let operand_span = operand.as_span();
+                let s = match lower_exp(operand, scope)? {
+                    AstNode::J(j) => j.into_series().map_err(|e| raise_error(e, operand_span))?,
+                    _ => {
+                        return Err(raise_error(
+                            "'IN' list requires literals".to_owned(),
+                            operand_span,
+                        ))
+                    }
+                };
+                series = match series {
+                    None => Some(s),
+                    Some(mut series) => {
+                        series
+                            .append(&s)
+                            .map_err(|e| raise_error(e.to_string(), operand_span))?;
+                        Some(series)
+                    }
+                }
+            }
+            scope.bin_op("in", start, lhs, AstNode::J(J::Series(series.unwrap())))
+        }
+        Rule::Between => {
+            let low = lower_exp(operands[0].clone(), scope)?;
+            let high = lower_exp(operands[1].clone(), scope)?;
+            scope.bin_op("between", start, lhs, AstNode::List(vec![low, high]))
+        }
+        Rule::Like => match lower_exp(operands[0].clone(), scope)? {
+            AstNode::J(J::String(pattern)) => scope.bin_op(
+                "like",
+                start,
+                lhs,
+                AstNode::J(J::String(like_to_regex(&pattern))),
+            ),
+            _ => {
+                return Err(raise_error(
+                    "'LIKE' requires a string pattern".to_owned(),
+                    operands[0].as_span(),
+                ))
+            }
+        },
+        Rule::IsNull => scope.unary_op("isnull", start, lhs),
+        unexpected_exp => {
+            return Err(raise_error(
+                format!("Unexpected rule: {:?}", unexpected_exp),
+                span,
+            ))
+        }
+    };
+    if is_not {
+        Ok(scope.unary_op("not", start, exp))
+    } else {
+        Ok(exp)
+    }
+}
+
+fn lower_function(pair: Pair<Rule>, scope: &Scope) -> AnsiResult<AstNode> {
+    let span = pair.as_span();
+    let mut pairs = pair.into_inner();
+    let name_pair = pairs.next().unwrap();
+    let name = name_pair.as_str().to_lowercase();
+    let f = match map_function(&name) {
+        Some(f) => f,
+        None => {
+            return Err(raise_error(
+                format!("unsupported function '{}'", name_pair.as_str()),
+                name_pair.as_span(),
+            ))
+        }
+    };
+    let args_pair = pairs.next().unwrap();
+    if let Some(over) = pairs.next() {
+        return Err(raise_error(
+            "window function is not supported, use '~over' in jasmine sql".to_owned(),
+            over.as_span(),
+        ));
+    }
+    let mut args = Vec::new();
+    for arg in args_pair.into_inner() {
+        match arg.as_rule() {
+            Rule::DISTINCT => {
+                return Err(raise_error(
+                    format!("'DISTINCT' in '{}' is not supported", name_pair.as_str()),
+                    arg.as_span(),
+                ))
+            }
+            Rule::ALL => {}
+            Rule::Star => {
+                if f != "count" {
+                    return Err(raise_error(
+                        format!("'*' is not supported in '{}'", name_pair.as_str()),
+                        arg.as_span(),
+                    ));
+                }
+                // 'i' is the row index in jasmine sql
+                args.push(scope.id("i", arg.as_span().start()))
+            }
+            _ => args.push(lower_exp(arg, scope)?),
+        }
+    }
+    Ok(AstNode::Call {
+        f: Box::new(scope.id(f, name_pair.as_span().start())),
+        args,
+        start: span.start(),
+        source_id: scope.source_id,
+    })
+}
+
+fn map_function(name: &str) -> Option<&'static str> {
+    let f = match name {
+        "avg" => "mean",
+        "count" => "count",
+        "sum" => "sum",
+        "min" => "min",
+        "max" => "max",
+        "median" => "median",
+        "stddev" | "stddev_samp" => "std1",
+        "stddev_pop" => "std0",
+        "variance" | "var_samp" => "var1",
+        "var_pop" => "var0",
+        "abs" => "abs",
+        "round" => "round",
+        "floor" => "floor",
+        "ceil" | "ceiling" => "ceil",
+        "sqrt" => "sqrt",
+        "exp" => "exp",
+        "ln" => "ln",
+        "log10" => "log10",
+        "sign" => "sign",
+        "lower" => "lowercase",
+        "upper" => "uppercase",
+        "trim" => "strip",
+        _ => return None,
+    };
+    Some(f)
+}
+
+// ordinal position in 'GROUP BY' and 'ORDER BY', starts from 1
+// source text without whitespaces, the same expressions at different positions are equal
+fn source_text(pair: &Pair<Rule>) -> String {
+    pair.as_str().split_whitespace().collect()
+}
+
+fn position(pair: &Pair<Rule>, len: usize) -> AnsiResult<Option<usize>> {
+    let s = pair.as_str();
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return Ok(None);
+    }
+    match s.parse::<usize>() {
+        Ok(i) if i >= 1 && i <= len => Ok(Some(i - 1)),
+        _ => Err(raise_error(
+            format!("position {} is out of range 1..{}", s, len),
+            pair.as_span(),
+        )),
+    }
+}
+
+fn lower_integer(pair: &Pair<Rule>) -> AnsiResult<J> {
+    pair.as_str()
+        .parse::<i64>()
+        .map(J::I64)
+        .map_err(|e| raise_error(format!("'{}': {}", pair.as_str(), e), pair.as_span()))
+}
+
+fn like_to_regex(pattern: &str) -> String {
+    let mut regex = "^".to_owned();
+    let mut literal = String::new();
+    for c in pattern.chars() {
+        match c {
+            '%' | '_' => {
+                regex.push_str(&regex::escape(&literal));
+                literal.clear();
+                regex.push_str(if c == '%' { ".*" } else { "." });
+            }
+            c => literal.push(c),
+        }
+    }
+    regex.push_str(&regex::escape(&literal));
+    regex.push('$');
+    regex
+}
+
+fn unquote(name: &str) -> String {
+    if name.starts_with('"') && name.ends_with('"') && name.len() > 1 {
+        name[1..name.len() - 1].to_owned()
+    } else {
+        name.to_owned()
+    }
+}
+
+fn unquote_string(s: &str) -> String {
+    s[1..s.len() - 1].replace("''", "'")
+}
+
+fn normalize(keyword: &str) -> String {
+    keyword
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_uppercase()
+}
+
+fn unsupported(pair: &Pair<Rule>) -> PestError<Rule> {
+    raise_error(
+        format!("'{}' is not supported", normalize(pair.as_str())),
+        pair.as_span(),
+    )
+}
+
+fn raise_error(msg: String, span: Span) -> PestError<Rule> {
+    PestError::new_from_span(ErrorVariant::CustomError { message: msg }, span)
+}
@@ -2,6 +2,7 @@ use crate::errors::{JError, JResult};
 use crate::j::J;
 
 #[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AstNode {
     J(J),
     Fn {
@@ -88,9 +89,69 @@ pub enum AstNode {
         ops: Vec<AstNode>,
         sorts: Vec<AstNode>,
         take: Box<AstNode>,
+        // first n rows of each group
+        take_by: Vec<AstNode>,
+        skip: Box<AstNode>,
+        distinct: bool,
         source_id: usize,
         start: usize,
     },
+    // kind: scalar, in, not in
+    SubQuery {
+        kind: String,
+        exp: Box<AstNode>,
+        sql: Box<AstNode>,
+        start: usize,
+        source_id: usize,
+    },
+    // common table expressions, names are only bound while evaluating sql
+    With {
+        names: Vec<String>,
+        sqls: Vec<AstNode>,
+        sql: Box<AstNode>,
+        start: usize,
+        source_id: usize,
+    },
+    // returns the logical and optimized plans of sql instead of the result
+    Explain {
+        sql: Box<AstNode>,
+        start: usize,
+        source_id: usize,
+    },
+    // kind: pivot, unpivot
+    // sql selects index, 'for' and value series, 'index' is the number of index series
+    Pivot {
+        kind: String,
+        sql: Box<AstNode>,
+        index: usize,
+        name: String,
+        start: usize,
+        source_id: usize,
+    },
+    // kind: insert, upsert, rows of upsert are matched by keys
+    Insert {
+        kind: String,
+        table: String,
+        keys: Vec<String>,
+        exp: Box<AstNode>,
+        start: usize,
+        source_id: usize,
+    },
+    // op: union, union all, intersect, except
+    SetOp {
+        op: String,
+        lhs: Box<AstNode>,
+        rhs: Box<AstNode>,
+        start: usize,
+        source_id: usize,
+    },
+    // placeholder of parameterized queries, name is '1' for '$1'
+    Param {
+        name: String,
+        start: usize,
+        end: usize,
+        source_id: usize,
+    },
     Skip,
 }
 
@@ -102,4 +163,64 @@ impl AstNode {
             Err(JError::ParserErr("Failed to cast to J".to_owned()))
         }
     }
+
+    // source id and byte offset of a node, operations are located at their operators
+    pub fn span(&self) -> Option<(usize, usize)> {
+        match self {
+            AstNode::Fn {
+                start, source_id, ..
+            }
+            | AstNode::Op {
+                start, source_id, ..
+            }
+            | AstNode::Id {
+                start, source_id, ..
+            }
+            | AstNode::Call {
+                start, source_id, ..
+            }
+            | AstNode::Raise {
+                start, source_id, ..
+            }
+            | AstNode::Dataframe {
+                start, source_id, ..
+            }
+            | AstNode::Sql {
+                start, source_id, ..
+            }
+            | AstNode::SubQuery {
+                start, source_id, ..
+            }
+            | AstNode::With {
+                start, source_id, ..
+            }
+            | AstNode::Explain {
+                start, source_id, ..
+            }
+            | AstNode::Pivot {
+                start, source_id, ..
+            }
+            | AstNode::Insert {
+                start, source_id, ..
+            }
+            | AstNode::SetOp {
+                start, source_id, ..
+            }
+            | AstNode::Param {
+                start, source_id, ..
+            } => Some((*source_id, *start)),
+            AstNode::UnaryOp { op, exp } => op.span().or_else(|| exp.span()),
+            AstNode::BinOp { op, lhs, rhs } => {
+                op.span().or_else(|| lhs.span()).or_else(|| rhs.span())
+            }
+            AstNode::Assign { exp, .. } | AstNode::Series { exp, .. } => exp.span(),
+            AstNode::IndexAssign { id, .. } => id.span(),
+            AstNode::If { cond, .. } | AstNode::While { cond, .. } => cond.span(),
+            AstNode::Return(exp) => exp.span(),
+            AstNode::Try { tries, .. } => tries.iter().find_map(|n| n.span()),
+            AstNode::Matrix(nodes) | AstNode::List(nodes) => nodes.iter().find_map(|n| n.span()),
+            AstNode::Dict { values, .. } => values.iter().find_map(|n| n.span()),
+            AstNode::J(_) | AstNode::Skip => None,
+        }
+    }
 }
@@ -0,0 +1,27 @@
+use std::collections::HashMap;
+
+use crate::{
+    errors::{JError, JResult},
+    j::J,
+    visit::{fold_children, fold_nodes, Fold},
+    AstNode,
+};
+
+// replaces placeholders with their values, '$1' is bound by key '1' and '$name' by 'name'
+pub fn bind(nodes: Vec<AstNode>, params: &HashMap<String, J>) -> JResult<Vec<AstNode>> {
+    fold_nodes(&mut Binder(params), nodes)
+}
+
+struct Binder<'a>(&'a HashMap<String, J>);
+
+impl Fold for Binder<'_> {
+    fn fold_node(&mut self, node: AstNode) -> JResult<AstNode> {
+        match node {
+            AstNode::Param { name, .. } => match self.0.get(&name) {
+                Some(j) => Ok(AstNode::J(j.clone())),
+                None => Err(JError::Err(format!("parameter '${}' is not bound", name))),
+            },
+            node => fold_children(self, node),
+        }
+    }
+}
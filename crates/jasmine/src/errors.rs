@@ -1,6 +1,8 @@
 use anyhow::Result;
 use thiserror::Error;
 
+use crate::source_map::locate;
+
 #[derive(Debug, Error)]
 pub enum JError {
     #[error("{0}")]
@@ -14,45 +16,22 @@ pub enum JError {
 
     #[error("length error '{0}' vs '{1}'")]
     MismatchedLengthErr(usize, usize),
+
+    #[error("shape error '{}x{}' vs '{}x{}'", .0.0, .0.1, .1.0, .1.1)]
+    MismatchedShapeErr((usize, usize), (usize, usize)),
+
+    #[error("singular matrix")]
+    SingularMatrixErr,
+
+    // valid but not supported by the sql planner, evaluated by jasminum instead
+    #[error("{0}")]
+    UnsupportedErr(String),
 }
 
 pub type JResult<J> = Result<J, JError>;
 
 pub fn trace(source: &str, path: &str, pos: usize, msg: &str) -> String {
-    let mut start = 0;
-    let mut r = 1;
-    let mut c = 1;
-    let mut chars = source.chars().peekable();
-    let mut i = 0;
-    while i < pos {
-        match chars.next() {
-            Some('\r') => {
-                if let Some(&'\n') = chars.peek() {
-                    chars.next();
-                    i += 2;
-                    r += 1;
-                    c = 1;
-                    start = i;
-                } else {
-                    i += 1;
-                    c += 1;
-                }
-            }
-            Some('\n') => {
-                i += 1;
-                r += 1;
-                c = 1;
-                start = i;
-            }
-            Some(ch) => {
-                i += ch.len_utf8();
-                c += 1;
-            }
-            None => {
-                break;
-            }
-        }
-    }
+    let (r, c, start) = locate(source, pos);
     let end = match &source[pos..].chars().position(|c| c == '\n' || c == '\r') {
         Some(i) => pos + i,
         None => source.len(),
@@ -0,0 +1,432 @@
+use std::io::Cursor;
+
+use indexmap::IndexMap;
+use ndarray::{ArcArray2, Array2, ShapeBuilder};
+use polars::{
+    frame::DataFrame,
+    io::{SerReader, SerWriter},
+    prelude::{IntoColumn, IpcCompression, IpcReader, IpcWriter},
+};
+
+use crate::{
+    errors::{JError, JResult},
+    j::J,
+};
+
+// message kinds, the 2nd byte of the header
+pub const ASYNC: u8 = 0;
+pub const SYNC: u8 = 1;
+pub const RESPONSE: u8 = 2;
+
+// type codes, the same as JType of python
+const NULL: u8 = 0;
+const BOOLEAN: u8 = 1;
+const INT: u8 = 2;
+const DATE: u8 = 3;
+const TIME: u8 = 4;
+const DATETIME: u8 = 5;
+const TIMESTAMP: u8 = 6;
+const DURATION: u8 = 7;
+const FLOAT: u8 = 8;
+const STRING: u8 = 9;
+const CAT: u8 = 10;
+const SERIES: u8 = 11;
+const MATRIX: u8 = 12;
+const LIST: u8 = 13;
+const DICT: u8 = 14;
+const DATAFRAME: u8 = 15;
+const ERR: u8 = 16;
+
+// layouts of matrix values
+const ROW_MAJOR: u8 = 0;
+const COLUMN_MAJOR: u8 = 1;
+
+// arrow ipc of an estimated size larger than 4MB is compressed by zstd if 'compress'
+const COMPRESS_SIZE: usize = 4_000_000;
+
+// nested lists and dicts deeper than this are rejected instead of overflowing the stack
+const MAX_DEPTH: usize = 128;
+
+// byte lengths are written as u32
+fn u32_len(len: usize) -> JResult<u32> {
+    u32::try_from(len).map_err(|_| {
+        JError::Err(format!(
+            "byte length {} exceeds the max length {} of a message",
+            len,
+            u32::MAX
+        ))
+    })
+}
+
+// 1 - little endian, kind, 2 reserved bytes and byte length of the message
+pub fn header(kind: u8, len: usize) -> JResult<[u8; 8]> {
+    let len = u32_len(len)?.to_le_bytes();
+    Ok([1, kind, 0, 0, len[0], len[1], len[2], len[3]])
+}
+
+// kind and byte length of the message
+pub fn parse_header(bytes: &[u8]) -> JResult<(u8, usize)> {
+    if bytes.len() < 8 {
+        return Err(JError::Err(format!(
+            "requires an 8 bytes header, got {} bytes",
+            bytes.len()
+        )));
+    }
+    if bytes[0] != 1 {
+        return Err(JError::Err(
+            "only little endian messages are supported".to_owned(),
+        ));
+    }
+    if bytes[1] > RESPONSE {
+        return Err(JError::Err(format!("unknown message kind {}", bytes[1])));
+    }
+    Ok((bytes[1], read_u32(bytes, 4)? as usize))
+}
+
+fn pad(buf: &mut Vec<u8>) {
+    buf.resize(buf.len().next_multiple_of(8), 0);
+}
+
+fn write_str(buf: &mut Vec<u8>, code: u8, s: &str) -> JResult<()> {
+    buf.extend((code as u32).to_le_bytes());
+    buf.extend(u32_len(s.len())?.to_le_bytes());
+    buf.extend(s.as_bytes());
+    pad(buf);
+    Ok(())
+}
+
+fn write_ipc(buf: &mut Vec<u8>, code: u8, df: &DataFrame, compress: bool) -> JResult<()> {
+    buf.extend((code as u32).to_le_bytes());
+    let len_start = buf.len();
+    buf.extend([0u8; 4]);
+    // the same estimate as python, type code, length, 1.1 times of data size and padding
+    let estimated_size = 16 + (df.estimated_size() as f64 * 1.1) as usize;
+    let compression = if compress && estimated_size > COMPRESS_SIZE {
+        Some(IpcCompression::ZSTD)
+    } else {
+        None
+    };
+    let mut cursor = Cursor::new(Vec::new());
+    IpcWriter::new(&mut cursor)
+        .with_compression(compression)
+        .finish(&mut df.clone())
+        .map_err(|e| JError::Err(e.to_string()))?;
+    let ipc = cursor.into_inner();
+    buf[len_start..len_start + 4].copy_from_slice(&u32_len(ipc.len())?.to_le_bytes());
+    buf.extend(ipc);
+    pad(buf);
+    Ok(())
+}
+
+// code, byte length, rows, columns, layout and 7 reserved bytes, then f64 values in the layout
+fn write_matrix(buf: &mut Vec<u8>, m: &ArcArray2<f64>) -> JResult<()> {
+    buf.extend((MATRIX as u32).to_le_bytes());
+    buf.extend(u32_len(16 + 8 * m.len())?.to_le_bytes());
+    buf.extend(u32_len(m.nrows())?.to_le_bytes());
+    buf.extend(u32_len(m.ncols())?.to_le_bytes());
+    // column major matrices, e.g. transposed ones, are written without reordering
+    let column_major = !m.is_standard_layout() && m.t().is_standard_layout();
+    let (layout, values) = if column_major {
+        (COLUMN_MAJOR, m.t())
+    } else {
+        (ROW_MAJOR, m.view())
+    };
+    buf.extend([layout, 0, 0, 0, 0, 0, 0, 0]);
+    values.iter().for_each(|v| buf.extend(v.to_le_bytes()));
+    Ok(())
+}
+
+// the byte format of 'docs/ipc.md', each value is aligned by 8 bytes
+pub fn serialize(j: &J, compress: bool) -> JResult<Vec<u8>> {
+    let mut buf = Vec::new();
+    write_j(&mut buf, j, compress)?;
+    Ok(buf)
+}
+
+fn write_j(buf: &mut Vec<u8>, j: &J, compress: bool) -> JResult<()> {
+    match j {
+        J::Null | J::TypedNull(_) => buf.extend([0u8; 8]),
+        J::Boolean(b) => buf.extend([BOOLEAN, 0, 0, 0, *b as u8, 0, 0, 0]),
+        // type codes of 8 bytes values take 8 bytes
+        J::I64(v) | J::Time(v) | J::Duration(v) => {
+            let code = match j {
+                J::I64(_) => INT,
+                J::Time(_) => TIME,
+                _ => DURATION,
+            };
+            buf.extend((code as u64).to_le_bytes());
+            buf.extend(v.to_le_bytes());
+        }
+        J::F64(f) => {
+            buf.extend((FLOAT as u64).to_le_bytes());
+            buf.extend(f.to_le_bytes());
+        }
+        J::Date(d) => {
+            buf.extend((DATE as u32).to_le_bytes());
+            buf.extend(d.to_le_bytes());
+        }
+        // dates of their first days as in python
+        J::Month(_) | J::Quarter(_) | J::Week(_) => {
+            write_j(buf, &J::Date(j.first_day().unwrap()), compress)?
+        }
+        J::Datetime {
+            ms: v,
+            timezone: tz,
+        }
+        | J::Timestamp {
+            ns: v,
+            timezone: tz,
+        } => {
+            let code = if let J::Datetime { .. } = j {
+                DATETIME
+            } else {
+                TIMESTAMP
+            };
+            buf.extend((code as u32).to_le_bytes());
+            buf.extend(u32_len(tz.len() + 8)?.to_le_bytes());
+            buf.extend(v.to_le_bytes());
+            buf.extend(tz.as_bytes());
+            pad(buf);
+        }
+        J::String(s) => write_str(buf, STRING, s)?,
+        J::Cat(s) => write_str(buf, CAT, s)?,
+        J::Err(s) => write_str(buf, ERR, s)?,
+        J::Series(s) => {
+            let df = DataFrame::new(vec![s.clone().into_column()])
+                .map_err(|e| JError::Err(e.to_string()))?;
+            write_ipc(buf, SERIES, &df, compress)?
+        }
+        J::DataFrame(df) => write_ipc(buf, DATAFRAME, df, compress)?,
+        J::MixedList(items) => {
+            let start = buf.len();
+            buf.extend((LIST as u32).to_le_bytes());
+            buf.extend([0u8; 4]);
+            buf.extend((items.len() as u64).to_le_bytes());
+            for item in items {
+                write_j(buf, item, compress)?;
+            }
+            let len = u32_len(buf.len() - start - 8)?;
+            buf[start + 4..start + 8].copy_from_slice(&len.to_le_bytes());
+        }
+        // code, byte length, item length, byte length of keys, end offsets of keys, keys,
+        // padding, byte length of values(8 bytes) and values
+        J::Dict(dict) => {
+            let start = buf.len();
+            buf.extend((DICT as u32).to_le_bytes());
+            buf.extend([0u8; 4]);
+            buf.extend(u32_len(dict.len())?.to_le_bytes());
+            buf.extend([0u8; 4]);
+            let mut offset = 0;
+            for k in dict.keys() {
+                offset += k.len();
+                buf.extend(u32_len(offset)?.to_le_bytes());
+            }
+            dict.keys().for_each(|k| buf.extend(k.as_bytes()));
+            let keys_len = u32_len(buf.len() - start - 16)?;
+            pad(buf);
+            let values_start = buf.len();
+            buf.extend([0u8; 8]);
+            for v in dict.values() {
+                write_j(buf, v, compress)?;
+            }
+            let len = u32_len(buf.len() - start - 8)?;
+            let values_len = u32_len(buf.len() - values_start - 8)?;
+            buf[start + 4..start + 8].copy_from_slice(&len.to_le_bytes());
+            buf[start + 12..start + 16].copy_from_slice(&keys_len.to_le_bytes());
+            buf[values_start..values_start + 4].copy_from_slice(&values_len.to_le_bytes());
+        }
+        J::Matrix(m) => write_matrix(buf, m)?,
+        J::Period { .. } => {
+            return Err(JError::Err(format!(
+                "unsupported j type for serialization: {}",
+                j.get_type_name()
+            )))
+        }
+    }
+    Ok(())
+}
+
+fn slice(bytes: &[u8], start: usize, len: usize) -> JResult<&[u8]> {
+    bytes.get(start..start + len).ok_or_else(|| {
+        JError::Err(format!(
+            "truncated message, requires {} bytes, got {}",
+            start + len,
+            bytes.len()
+        ))
+    })
+}
+
+fn read_u32(bytes: &[u8], start: usize) -> JResult<u32> {
+    Ok(u32::from_le_bytes(
+        slice(bytes, start, 4)?.try_into().unwrap(),
+    ))
+}
+
+fn read_i64(bytes: &[u8], start: usize) -> JResult<i64> {
+    Ok(i64::from_le_bytes(
+        slice(bytes, start, 8)?.try_into().unwrap(),
+    ))
+}
+
+fn read_str(bytes: &[u8], start: usize, len: usize) -> JResult<String> {
+    String::from_utf8(slice(bytes, start, len)?.to_vec())
+        .map_err(|e| JError::Err(format!("invalid utf-8 string, {}", e)))
+}
+
+fn read_ipc(bytes: &[u8], start: usize) -> JResult<(DataFrame, usize)> {
+    let len = read_u32(bytes, start + 4)? as usize;
+    let ipc = slice(bytes, start + 8, len)?;
+    let df = IpcReader::new(Cursor::new(ipc))
+        .finish()
+        .map_err(|e| JError::Err(e.to_string()))?;
+    Ok((df, 8 + len))
+}
+
+// values are copied into a matrix of the layout of the message
+fn read_matrix(bytes: &[u8], start: usize) -> JResult<(Array2<f64>, usize)> {
+    let len = read_u32(bytes, start + 4)? as usize;
+    let rows = read_u32(bytes, start + 8)? as usize;
+    let cols = read_u32(bytes, start + 12)? as usize;
+    let layout = slice(bytes, start + 16, 1)?[0];
+    let expected_len = rows
+        .checked_mul(cols)
+        .and_then(|n| n.checked_mul(8)?.checked_add(16));
+    if expected_len != Some(len) {
+        return Err(JError::Err(format!(
+            "invalid byte length {} of a '{}x{}' matrix",
+            len, rows, cols
+        )));
+    }
+    if layout != ROW_MAJOR && layout != COLUMN_MAJOR {
+        return Err(JError::Err(format!("unknown layout of matrix {}", layout)));
+    }
+    let payload = slice(bytes, start + 24, len - 16)?;
+    let shape = (rows, cols).set_f(layout == COLUMN_MAJOR);
+    let values = payload
+        .chunks_exact(8)
+        .map(|b| f64::from_le_bytes(b.try_into().unwrap()))
+        .collect();
+    let m = Array2::from_shape_vec(shape, values).map_err(|e| JError::Err(e.to_string()))?;
+    Ok((m, 8 + len))
+}
+
+pub fn deserialize(bytes: &[u8]) -> JResult<J> {
+    Ok(read_j(bytes, 0, 0)?.0)
+}
+
+// a value at 'start' nested in 'depth' lists or dicts, and its byte length including padding
+fn read_j(bytes: &[u8], start: usize, depth: usize) -> JResult<(J, usize)> {
+    let code = *slice(bytes, start, 1)?.first().unwrap();
+    let (j, len) = match code {
+        NULL => (J::Null, 8),
+        BOOLEAN => (J::Boolean(slice(bytes, start + 4, 1)?[0] != 0), 8),
+        INT => (J::I64(read_i64(bytes, start + 8)?), 16),
+        TIME => (J::Time(read_i64(bytes, start + 8)?), 16),
+        DURATION => (J::Duration(read_i64(bytes, start + 8)?), 16),
+        FLOAT => (
+            J::F64(f64::from_bits(read_i64(bytes, start + 8)? as u64)),
+            16,
+        ),
+        DATE => (J::Date(read_u32(bytes, start + 4)? as i32), 8),
+        DATETIME | TIMESTAMP => {
+            let len = read_u32(bytes, start + 4)? as usize;
+            if len < 8 {
+                return Err(JError::Err(format!(
+                    "requires at least 8 bytes for a datetime, got {}",
+                    len
+                )));
+            }
+            let v = read_i64(bytes, start + 8)?;
+            let timezone = read_str(bytes, start + 16, len - 8)?;
+            let j = if code == DATETIME {
+                J::Datetime { ms: v, timezone }
+            } else {
+                J::Timestamp { ns: v, timezone }
+            };
+            (j, 8 + len)
+        }
+        STRING | CAT | ERR => {
+            let len = read_u32(bytes, start + 4)? as usize;
+            let s = read_str(bytes, start + 8, len)?;
+            let j = match code {
+                STRING => J::String(s),
+                CAT => J::Cat(s),
+                _ => J::Err(s),
+            };
+            (j, 8 + len)
+        }
+        SERIES => {
+            let (df, len) = read_ipc(bytes, start)?;
+            if df.width() != 1 {
+                return Err(JError::MismatchedLengthErr(1, df.width()));
+            }
+            let series = df.take_columns().swap_remove(0).take_materialized_series();
+            (J::Series(series), len)
+        }
+        // keeps the layout of the message
+        MATRIX => {
+            let (m, len) = read_matrix(bytes, start)?;
+            (J::Matrix(m.into_shared()), len)
+        }
+        DATAFRAME => {
+            let (df, len) = read_ipc(bytes, start)?;
+            (J::DataFrame(df), len)
+        }
+        LIST | DICT if depth >= MAX_DEPTH => {
+            return Err(JError::Err(format!(
+                "exceeds the max nesting depth {} of lists and dicts",
+                MAX_DEPTH
+            )))
+        }
+        LIST => {
+            let len = read_u32(bytes, start + 4)? as usize;
+            let count = read_i64(bytes, start + 8)? as usize;
+            let mut offset = start + 16;
+            let mut items = Vec::with_capacity(count.min(len / 8));
+            for _ in 0..count {
+                let (item, item_len) = read_j(bytes, offset, depth + 1)?;
+                items.push(item);
+                offset += item_len;
+            }
+            (J::MixedList(items), 8 + len)
+        }
+        DICT => {
+            let len = read_u32(bytes, start + 4)? as usize;
+            let count = read_u32(bytes, start + 8)? as usize;
+            let keys_len = read_u32(bytes, start + 12)? as usize;
+            let keys_start = start + 16 + 4 * count;
+            let mut keys = Vec::with_capacity(count.min(keys_len / 4));
+            let mut key_start = 0;
+            for i in 0..count {
+                let key_end = read_u32(bytes, start + 16 + 4 * i)? as usize;
+                if key_end < key_start {
+                    return Err(JError::Err(format!(
+                        "invalid offset of dict keys, {} < {}",
+                        key_end, key_start
+                    )));
+                }
+                keys.push(read_str(
+                    bytes,
+                    keys_start + key_start,
+                    key_end - key_start,
+                )?);
+                key_start = key_end;
+            }
+            let mut offset = (start + 16 + keys_len).next_multiple_of(8) + 8;
+            let mut dict = IndexMap::with_capacity(keys.len());
+            for key in keys {
+                let (value, value_len) = read_j(bytes, offset, depth + 1)?;
+                dict.insert(key, value);
+                offset += value_len;
+            }
+            (J::Dict(dict), 8 + len)
+        }
+        _ => {
+            return Err(JError::Err(format!(
+                "unsupported j type for deserialization: {}",
+                code
+            )))
+        }
+    };
+    Ok((j, len.next_multiple_of(8)))
+}
@@ -1,22 +1,35 @@
+use chrono::{Datelike, NaiveDate};
 use indexmap::IndexMap;
 use ndarray::ArcArray2;
 use polars::{
     frame::DataFrame,
-    prelude::{CategoricalOrdering, DataType, NamedFrom, TimeUnit},
+    prelude::{
+        AnyValue, CategoricalOrdering, DataType, IntoSeries, ListChunked, NamedFrom, StructChunked,
+        TimeUnit,
+    },
     series::Series,
 };
 
+use crate::parser::UNIX_EPOCH_DAY;
+
+// series and dataframes are serialized as Arrow IPC bytes by polars
 #[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum J {
     Null,
+    TypedNull(DataType), // a null of the data type, e.g. 0Ni64
 
     Boolean(bool),
     I64(i64),
     Date(i32),                               // start from 1970.01.01
+    Month(i32),                              // start from 1970.01, e.g. 2024-03m
+    Quarter(i32),                            // start from 1970Q1, e.g. 2024Q1
+    Week(i32),                               // start from 1970W01, e.g. 2024W10
     Time(i64),                               // 00:00:00.0 - 23:59:59.999999999
     Datetime { ms: i64, timezone: String },  // start from 1970.01.01T00:00:00.0
     Timestamp { ns: i64, timezone: String }, // start from 1970.01.01D00:00:00.0
     Duration(i64),
+    Period { months: i32, days: i32, ns: i64 }, // calendar durations, e.g. 1mo, 1w
     F64(f64),
     String(String),
     Cat(String),
@@ -41,6 +54,12 @@ impl J {
             J::Date(s) => Ok(Series::new("".into(), vec![*s])
                 .cast(&DataType::Date)
                 .unwrap()),
+            // the first day of months, quarters and weeks
+            J::Month(_) | J::Quarter(_) | J::Week(_) => {
+                Ok(Series::new("".into(), vec![self.first_day().unwrap()])
+                    .cast(&DataType::Date)
+                    .unwrap())
+            }
             J::Timestamp { ns, timezone } => Ok(Series::new("".into(), vec![*ns])
                 .cast(&DataType::Datetime(
                     TimeUnit::Nanoseconds,
@@ -59,15 +78,49 @@ impl J {
             J::Duration(s) => Ok(Series::new("".into(), vec![*s])
                 .cast(&DataType::Duration(TimeUnit::Nanoseconds))
                 .unwrap()),
+            J::Period { months, days, ns } => period_series(&[Some((*months, *days, *ns))]),
             J::Cat(s) => Ok(Series::new("".into(), vec![s.to_owned()])
                 .cast(&DataType::Categorical(None, CategoricalOrdering::Lexical))
                 .unwrap()),
             J::String(s) => Ok(Series::new("".into(), vec![s.to_owned()])),
             J::Null => Ok(Series::new_null("".into(), 1)),
+            J::TypedNull(dtype) => Ok(Series::full_null("".into(), 1, dtype)),
+            J::MixedList(l) => list_into_series(l),
+            J::Dict(d) => {
+                let fields = d
+                    .iter()
+                    .map(|(k, j)| {
+                        let mut s = j.series().or_else(|_| j.into_series())?;
+                        s.rename(k.into());
+                        Ok(s)
+                    })
+                    .collect::<Result<Vec<_>, String>>()?;
+                let len = fields.iter().map(|s| s.len()).max().unwrap_or(0);
+                StructChunked::from_series("".into(), len, fields.iter())
+                    .map(|s| s.into_series())
+                    .map_err(|e| e.to_string())
+            }
             _ => Err("cannot turn into a series".to_owned()),
         }
     }
 
+    // days since 1970.01.01 of the first day of a month, quarter or week
+    pub fn first_day(&self) -> Option<i32> {
+        let month = match self {
+            J::Month(m) => *m,
+            J::Quarter(q) => q * 3,
+            // 1970W01 starts from 1969.12.29
+            J::Week(w) => return Some(w * 7 - 3),
+            _ => return None,
+        };
+        NaiveDate::from_ymd_opt(
+            1970 + month.div_euclid(12),
+            month.rem_euclid(12) as u32 + 1,
+            1,
+        )
+        .map(|d| d.num_days_from_ce() - UNIX_EPOCH_DAY)
+    }
+
     pub fn series(&self) -> Result<Series, String> {
         match self {
             J::Series(s) => Ok(s.clone()),
@@ -95,10 +148,14 @@ impl J {
             | J::I64(_)
             | J::F64(_)
             | J::Date(_)
+            | J::Month(_)
+            | J::Quarter(_)
+            | J::Week(_)
             | J::Timestamp { .. }
             | J::Datetime { .. }
             | J::Time(_)
             | J::Duration(_)
+            | J::Period { .. }
             | J::Cat(_)
             | J::String(_) => true,
             _ => false,
@@ -111,10 +168,14 @@ impl J {
             J::I64(_) => "i64".to_owned(),
             J::F64(_) => "f64".to_owned(),
             J::Date(_) => "date".to_owned(),
+            J::Month(_) => "month".to_owned(),
+            J::Quarter(_) => "quarter".to_owned(),
+            J::Week(_) => "week".to_owned(),
             J::Timestamp { .. } => "timestamp".to_owned(),
             J::Datetime { .. } => "datetime".to_owned(),
             J::Time(_) => "time".to_owned(),
             J::Duration(_) => "duration".to_owned(),
+            J::Period { .. } => "period".to_owned(),
             J::Cat(_) => "sym".to_owned(),
             J::String(_) => "str".to_owned(),
 
@@ -124,7 +185,107 @@ impl J {
             J::Dict(_) => "dict".to_owned(),
             J::DataFrame(_) => "df".to_owned(),
             J::Err(_) => "err".to_owned(),
-            J::Null => "null".to_owned(),
+            J::Null | J::TypedNull(_) => "null".to_owned(),
+        }
+    }
+}
+
+// a list of scalars is a series of values, otherwise each item is an entry of a list series
+fn list_into_series(l: &[J]) -> Result<Series, String> {
+    if l.iter()
+        .all(|j| j.is_scalar() || matches!(j, J::Null | J::TypedNull(_)))
+    {
+        let values = l
+            .iter()
+            .map(|j| j.into_series())
+            .collect::<Result<Vec<_>, String>>()?;
+        let dtype = first_dtype(values.iter());
+        if values
+            .iter()
+            .all(|s| s.dtype() == &dtype || s.dtype().is_null())
+        {
+            let mut series = Series::new_empty("".into(), &dtype);
+            for s in values {
+                series
+                    .append(&s.cast(&dtype).map_err(|e| e.to_string())?)
+                    .map_err(|e| e.to_string())?;
+            }
+            return Ok(series);
         }
+        // e.g. integers and floats
+        let values = values
+            .iter()
+            .map(|s| s.get(0).map(|v| v.into_static()))
+            .collect::<Result<Vec<AnyValue>, _>>()
+            .map_err(|e| e.to_string())?;
+        return Series::from_any_values("".into(), &values, false).map_err(|e| e.to_string());
+    }
+    let entries = l
+        .iter()
+        .map(|j| match j {
+            J::Null => Ok(None),
+            j => j.series().or_else(|_| j.into_series()).map(Some),
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let dtype = first_dtype(entries.iter().flatten());
+    let entries = entries
+        .into_iter()
+        .map(|entry| match entry {
+            Some(s) if s.dtype() != &dtype && !s.dtype().is_null() => Err(format!(
+                "requires list entries of the same data type, got {} and {}",
+                dtype,
+                s.dtype()
+            )),
+            Some(s) => s.cast(&dtype).map(Some).map_err(|e| e.to_string()),
+            None => Ok(None),
+        })
+        .collect::<Result<ListChunked, String>>()?;
+    Ok(entries.into_series())
+}
+
+fn first_dtype<'a>(mut series: impl Iterator<Item = &'a Series>) -> DataType {
+    series
+        .find(|s| !s.dtype().is_null())
+        .map_or(DataType::Null, |s| s.dtype().clone())
+}
+
+// a struct series of 'months', 'days' and 'ns'
+pub fn period_series(periods: &[Option<(i32, i32, i64)>]) -> Result<Series, String> {
+    let fields = [
+        Series::new(
+            "months".into(),
+            periods.iter().map(|p| p.map(|p| p.0)).collect::<Vec<_>>(),
+        ),
+        Series::new(
+            "days".into(),
+            periods.iter().map(|p| p.map(|p| p.1)).collect::<Vec<_>>(),
+        ),
+        Series::new(
+            "ns".into(),
+            periods.iter().map(|p| p.map(|p| p.2)).collect::<Vec<_>>(),
+        ),
+    ];
+    StructChunked::from_series("".into(), periods.len(), fields.iter())
+        .map(|s| s.into_series())
+        .map_err(|e| e.to_string())
+}
+
+// in the duration language of polars, e.g. '1y2mo', a period literal has a single sign
+pub fn format_period(months: i32, days: i32, ns: i64) -> String {
+    let sign = if months < 0 || days < 0 || ns < 0 {
+        "-"
+    } else {
+        ""
+    };
+    let mut period = sign.to_owned();
+    if months != 0 {
+        period += &format!("{}mo", months.unsigned_abs());
+    }
+    if days != 0 {
+        period += &format!("{}d", days.unsigned_abs());
+    }
+    if ns != 0 || period.len() == sign.len() {
+        period += &format!("{}ns", ns.unsigned_abs());
     }
+    period
 }
@@ -0,0 +1,12 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::errors::{JError, JResult};
+
+// e.g. parsed nodes to cache or to send to a web ui
+pub fn to_json<T: Serialize + ?Sized>(value: &T) -> JResult<String> {
+    serde_json::to_string(value).map_err(|e| JError::Err(format!("failed to serialize, {}", e)))
+}
+
+pub fn from_json<T: DeserializeOwned>(json: &str) -> JResult<T> {
+    serde_json::from_str(json).map_err(|e| JError::Err(format!("failed to deserialize, {}", e)))
+}
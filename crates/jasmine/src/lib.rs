@@ -1,8 +1,22 @@
+pub mod ansi;
 mod ast_node;
+pub mod bind;
 pub mod errors;
+pub mod ipc;
 pub mod j;
+#[cfg(feature = "serde")]
+pub mod json;
+pub mod matrix;
 mod parser;
+pub mod source_map;
+pub mod visit;
+pub use ansi::{parse_ansi_sql, parse_ansi_sql_with_options};
 pub use ast_node::AstNode;
+pub use bind::bind;
 pub use errors::trace;
+#[cfg(feature = "serde")]
+pub use json::{from_json, to_json};
 pub use parser::UNIX_EPOCH_DAY;
-pub use parser::{parse, JParser, Rule};
+pub use parser::{local_timezone, parse, parse_with_options, JParser, ParseOptions, Rule};
+pub use source_map::{Source, SourceMap};
+pub use visit::{Fold, Visitor, VisitorMut};
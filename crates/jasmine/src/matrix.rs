@@ -0,0 +1,343 @@
+use indexmap::IndexMap;
+use ndarray::{ArcArray2, Array2, Axis};
+use polars::{
+    frame::DataFrame,
+    prelude::{Column, DataType, Float64Type, IndexOrder, NamedFrom},
+    series::Series,
+};
+
+use crate::{
+    errors::{JError, JResult},
+    j::J,
+};
+
+type Matrix = ArcArray2<f64>;
+
+fn shape(m: &Matrix) -> (usize, usize) {
+    (m.nrows(), m.ncols())
+}
+
+fn polars_err(e: polars::error::PolarsError) -> JError {
+    JError::Err(e.to_string())
+}
+
+fn require_square(m: &Matrix) -> JResult<usize> {
+    if m.nrows() == m.ncols() {
+        Ok(m.nrows())
+    } else {
+        Err(JError::MismatchedShapeErr(shape(m), (m.nrows(), m.nrows())))
+    }
+}
+
+pub fn matmul(lhs: &Matrix, rhs: &Matrix) -> JResult<Matrix> {
+    if lhs.ncols() != rhs.nrows() {
+        return Err(JError::MismatchedShapeErr(shape(lhs), shape(rhs)));
+    }
+    Ok(lhs.dot(rhs).into_shared())
+}
+
+pub fn transpose(m: &Matrix) -> Matrix {
+    m.t().to_owned().into_shared()
+}
+
+// lu decomposition with partial pivoting, none if the matrix is singular
+struct Lu {
+    lu: Array2<f64>,
+    perm: Vec<usize>,
+    sign: f64,
+}
+
+fn decompose(m: &Matrix) -> JResult<Option<Lu>> {
+    let n = require_square(m)?;
+    let mut lu = m.to_owned();
+    let mut perm: Vec<usize> = (0..n).collect();
+    let mut sign = 1.0;
+    let scale = lu.iter().fold(0.0f64, |acc, v| acc.max(v.abs()));
+    let tolerance = f64::EPSILON * scale * n as f64;
+    for k in 0..n {
+        let pivot = (k..n)
+            .max_by(|&i, &j| lu[[i, k]].abs().total_cmp(&lu[[j, k]].abs()))
+            .unwrap();
+        if lu[[pivot, k]].abs() <= tolerance || lu[[pivot, k]].is_nan() {
+            return Ok(None);
+        }
+        if pivot != k {
+            for j in 0..n {
+                lu.swap([k, j], [pivot, j]);
+            }
+            perm.swap(k, pivot);
+            sign = -sign;
+        }
+        for i in k + 1..n {
+            let factor = lu[[i, k]] / lu[[k, k]];
+            lu[[i, k]] = factor;
+            for j in k + 1..n {
+                lu[[i, j]] -= factor * lu[[k, j]];
+            }
+        }
+    }
+    Ok(Some(Lu { lu, perm, sign }))
+}
+
+impl Lu {
+    fn solve(&self, b: &Matrix) -> Matrix {
+        let n = self.lu.nrows();
+        let mut x = Array2::<f64>::zeros((n, b.ncols()));
+        for c in 0..b.ncols() {
+            for i in 0..n {
+                let mut v = b[[self.perm[i], c]];
+                for j in 0..i {
+                    v -= self.lu[[i, j]] * x[[j, c]];
+                }
+                x[[i, c]] = v;
+            }
+            for i in (0..n).rev() {
+                let mut v = x[[i, c]];
+                for j in i + 1..n {
+                    v -= self.lu[[i, j]] * x[[j, c]];
+                }
+                x[[i, c]] = v / self.lu[[i, i]];
+            }
+        }
+        x.into_shared()
+    }
+}
+
+// 0 for singular matrices
+pub fn det(m: &Matrix) -> JResult<f64> {
+    match decompose(m)? {
+        Some(lu) => Ok(lu.sign * lu.lu.diag().product()),
+        None => Ok(0.0),
+    }
+}
+
+pub fn inverse(m: &Matrix) -> JResult<Matrix> {
+    let lu = decompose(m)?.ok_or(JError::SingularMatrixErr)?;
+    Ok(lu.solve(&Array2::eye(m.nrows()).into_shared()))
+}
+
+// x of 'a x = b', each column of 'b' is a right-hand side
+pub fn solve(a: &Matrix, b: &Matrix) -> JResult<Matrix> {
+    require_square(a)?;
+    if a.nrows() != b.nrows() {
+        return Err(JError::MismatchedShapeErr(shape(a), shape(b)));
+    }
+    let lu = decompose(a)?.ok_or(JError::SingularMatrixErr)?;
+    Ok(lu.solve(b))
+}
+
+fn apply(op: &str) -> JResult<fn(f64, f64) -> f64> {
+    match op {
+        "+" => Ok(|x, y| x + y),
+        "-" => Ok(|x, y| x - y),
+        "*" => Ok(|x, y| x * y),
+        "/" => Ok(|x, y| x / y),
+        "**" => Ok(f64::powf),
+        _ => Err(JError::Err(format!(
+            "unsupported operator for matrix '{}'",
+            op
+        ))),
+    }
+}
+
+fn f64_values(s: &Series) -> JResult<Vec<f64>> {
+    if !(s.dtype().is_primitive_numeric() || s.dtype().is_bool()) {
+        return Err(JError::Err(format!(
+            "requires numeric data type, got '{}'",
+            s.dtype()
+        )));
+    }
+    Ok(s.cast(&DataType::Float64)
+        .map_err(polars_err)?
+        .f64()
+        .map_err(polars_err)?
+        .iter()
+        .map(|v| v.unwrap_or(f64::NAN))
+        .collect())
+}
+
+// element-wise with a matrix of the same shape, a scalar, or a series of a value per column
+pub fn broadcast(op: &str, lhs: &J, rhs: &J) -> JResult<J> {
+    let f = apply(op)?;
+    let scalar = |j: &J| match j {
+        J::I64(i) => Some(*i as f64),
+        J::F64(f) => Some(*f),
+        J::Boolean(b) => Some(*b as i64 as f64),
+        _ => None,
+    };
+    let row_op = |m: &Matrix, s: &Series, flip: bool| -> JResult<J> {
+        let values = f64_values(s)?;
+        if values.len() != m.ncols() {
+            return Err(JError::MismatchedLengthErr(m.ncols(), values.len()));
+        }
+        let mut out = m.to_owned();
+        for mut row in out.axis_iter_mut(Axis(0)) {
+            row.iter_mut().zip(values.iter()).for_each(|(x, &y)| {
+                *x = if flip { f(y, *x) } else { f(*x, y) };
+            });
+        }
+        Ok(J::Matrix(out.into_shared()))
+    };
+    match (lhs, rhs) {
+        (J::Matrix(l), J::Matrix(r)) => {
+            if l.shape() != r.shape() {
+                return Err(JError::MismatchedShapeErr(shape(l), shape(r)));
+            }
+            let mut out = l.to_owned();
+            out.zip_mut_with(r, |x, &y| *x = f(*x, y));
+            Ok(J::Matrix(out.into_shared()))
+        }
+        (J::Matrix(m), J::Series(s)) => row_op(m, s, false),
+        (J::Series(s), J::Matrix(m)) => row_op(m, s, true),
+        (J::Matrix(m), j) if scalar(j).is_some() => {
+            let y = scalar(j).unwrap();
+            Ok(J::Matrix(m.mapv(|x| f(x, y)).into_shared()))
+        }
+        (j, J::Matrix(m)) if scalar(j).is_some() => {
+            let x = scalar(j).unwrap();
+            Ok(J::Matrix(m.mapv(|y| f(x, y)).into_shared()))
+        }
+        _ => Err(JError::Err(format!(
+            "unsupported operand types for '{}': '{}' and '{}'",
+            op,
+            lhs.get_type_name(),
+            rhs.get_type_name()
+        ))),
+    }
+}
+
+// nulls are nan
+pub fn from_dataframe(df: &DataFrame) -> JResult<Matrix> {
+    for column in df.get_columns() {
+        if !(column.dtype().is_primitive_numeric() || column.dtype().is_bool()) {
+            return Err(JError::Err(format!(
+                "requires numeric data type, got '{}' of '{}'",
+                column.dtype(),
+                column.name()
+            )));
+        }
+    }
+    Ok(df
+        .to_ndarray::<Float64Type>(IndexOrder::C)
+        .map_err(polars_err)?
+        .into_shared())
+}
+
+// series are named as matrix literals, 'col00', 'col01', ...
+pub fn to_dataframe(m: &Matrix) -> JResult<DataFrame> {
+    let columns: Vec<Column> = m
+        .axis_iter(Axis(1))
+        .enumerate()
+        .map(|(i, values)| Column::new(format!("col{:02}", i).into(), values.to_vec()))
+        .collect();
+    DataFrame::new(columns).map_err(polars_err)
+}
+
+// a column vector
+pub fn from_series(s: &Series) -> JResult<Matrix> {
+    let values = f64_values(s)?;
+    let n = values.len();
+    Ok(Array2::from_shape_vec((n, 1), values)
+        .unwrap()
+        .into_shared())
+}
+
+// a column or row vector
+pub fn to_series(m: &Matrix) -> JResult<Series> {
+    if m.ncols() != 1 && m.nrows() != 1 {
+        return Err(JError::MismatchedShapeErr(shape(m), (m.nrows(), 1)));
+    }
+    Ok(Series::new(
+        "".into(),
+        m.iter().copied().collect::<Vec<f64>>(),
+    ))
+}
+
+pub struct Lsq {
+    pub coef: Vec<f64>,
+    // none for dropped rows
+    pub residuals: Vec<Option<f64>>,
+    pub r2: f64,
+}
+
+impl Lsq {
+    pub fn into_j(self) -> J {
+        J::Dict(IndexMap::from([
+            (
+                "coef".to_owned(),
+                J::Series(Series::new("coef".into(), self.coef)),
+            ),
+            (
+                "residuals".to_owned(),
+                J::Series(Series::new("residuals".into(), self.residuals)),
+            ),
+            ("r2".to_owned(), J::F64(self.r2)),
+        ]))
+    }
+}
+
+// least squares of 'y = x b', ridge regression if 'ridge' is positive, rows with nulls or nan
+// are dropped if 'drop_nulls', otherwise they are errors
+pub fn lsq(y: &[f64], x: &Matrix, ridge: f64, drop_nulls: bool) -> JResult<Lsq> {
+    if y.len() != x.nrows() {
+        return Err(JError::MismatchedLengthErr(y.len(), x.nrows()));
+    }
+    if ridge.is_nan() || ridge < 0.0 {
+        return Err(JError::Err(format!(
+            "requires a non-negative ridge penalty, got {}",
+            ridge
+        )));
+    }
+    let is_valid = |i: usize| !y[i].is_nan() && x.row(i).iter().all(|v| !v.is_nan());
+    if !drop_nulls {
+        if let Some(row) = (0..y.len()).find(|&i| !is_valid(i)) {
+            return Err(JError::Err(format!(
+                "'lsq' requires values without nulls, got null at row {}",
+                row
+            )));
+        }
+    }
+    let valid: Vec<usize> = (0..y.len()).filter(|&i| is_valid(i)).collect();
+    let xv = x.select(Axis(0), &valid);
+    let yv = Array2::from_shape_fn((valid.len(), 1), |(i, _)| y[valid[i]]);
+    let mut xtx = xv.t().dot(&xv);
+    xtx.diag_mut().iter_mut().for_each(|v| *v += ridge);
+    let coef = solve(&xtx.into_shared(), &xv.t().dot(&yv).into_shared())?;
+    let coef: Vec<f64> = coef.iter().copied().collect();
+
+    let mut residuals = vec![None; y.len()];
+    let mut ss_res = 0.0;
+    for &i in valid.iter() {
+        let fitted: f64 = x.row(i).iter().zip(coef.iter()).map(|(a, b)| a * b).sum();
+        let residual = y[i] - fitted;
+        ss_res += residual * residual;
+        residuals[i] = Some(residual);
+    }
+    let mean = valid.iter().map(|&i| y[i]).sum::<f64>() / valid.len() as f64;
+    let ss_tot: f64 = valid.iter().map(|&i| (y[i] - mean).powi(2)).sum();
+    let r2 = if ss_tot == 0.0 {
+        f64::NAN
+    } else {
+        1.0 - ss_res / ss_tot
+    };
+    Ok(Lsq {
+        coef,
+        residuals,
+        r2,
+    })
+}
+
+// series of x are columns of the matrix
+pub fn lsq_series(y: &Series, xs: &[Series], ridge: f64, drop_nulls: bool) -> JResult<Lsq> {
+    let mut x = Array2::<f64>::zeros((y.len(), xs.len()));
+    for (j, s) in xs.iter().enumerate() {
+        if s.len() != y.len() {
+            return Err(JError::MismatchedLengthErr(y.len(), s.len()));
+        }
+        x.column_mut(j)
+            .iter_mut()
+            .zip(f64_values(s)?)
+            .for_each(|(v, value)| *v = value);
+    }
+    lsq(&f64_values(y)?, &x.into_shared(), ridge, drop_nulls)
+}
@@ -1,7 +1,7 @@
 use crate::ast_node::AstNode;
-use crate::j::J;
+use crate::j::{period_series, J};
+use chrono::Datelike;
 use chrono::{self, TimeZone};
-use chrono::{Datelike, Local};
 use indexmap::IndexMap;
 use pest::error::{Error as PestError, ErrorVariant};
 use pest::Span;
@@ -13,6 +13,8 @@ use polars::prelude::{Column, IndexOrder, NamedFrom};
 use polars::series::Series;
 use regex::bytes::Regex;
 use regex::RegexSet;
+use std::num::{IntErrorKind, ParseFloatError};
+use std::str::FromStr;
 
 pub const UNIX_EPOCH_DAY: i32 = 719_163;
 
@@ -22,7 +24,8 @@ pub const NS_IN_DAY: i64 = 86_400_000_000_000;
 #[grammar = "jasmine.pest"]
 pub struct JParser;
 
-fn parse_binary_op(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, PestError<Rule>> {
+fn parse_binary_op(pair: Pair<Rule>, opts: &ParseOptions) -> Result<AstNode, PestError<Rule>> {
+    let source_id = opts.source_id;
     match pair.as_rule() {
         Rule::BinaryOp => Ok(AstNode::Op {
             name: pair.as_str().to_owned(),
@@ -50,29 +53,30 @@ fn parse_binary_op(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, PestEr
     }
 }
 
-fn parse_exp(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, PestError<Rule>> {
+fn parse_exp(pair: Pair<Rule>, opts: &ParseOptions) -> Result<AstNode, PestError<Rule>> {
+    let source_id = opts.source_id;
     let rule = pair.as_rule();
     match rule {
-        Rule::Exp => parse_exp(pair.into_inner().next().unwrap(), source_id),
+        Rule::Exp => parse_exp(pair.into_inner().next().unwrap(), opts),
         Rule::UnaryExp | Rule::UnarySqlExp => {
             let mut pair = pair.into_inner();
             let unary = pair.next().unwrap();
             let exp = pair.next().unwrap();
-            let exp = parse_exp(exp, source_id)?;
+            let exp = parse_exp(exp, opts)?;
             Ok(AstNode::UnaryOp {
-                op: Box::new(parse_exp(unary, source_id)?),
+                op: Box::new(parse_exp(unary, opts)?),
                 exp: Box::new(exp),
             })
         }
         Rule::BinaryExp | Rule::BinarySqlExp => {
             let mut pair = pair.into_inner();
             let lhs_pair = pair.next().unwrap();
-            let lhs = parse_exp(lhs_pair, source_id)?;
+            let lhs = parse_exp(lhs_pair, opts)?;
             let binary_exp = pair.next().unwrap();
             let rhs_pair = pair.next().unwrap();
-            let rhs = parse_exp(rhs_pair, source_id)?;
+            let rhs = parse_exp(rhs_pair, opts)?;
             Ok(AstNode::BinOp {
-                op: Box::new(parse_binary_op(binary_exp, source_id)?),
+                op: Box::new(parse_binary_op(binary_exp, opts)?),
                 lhs: Box::new(lhs),
                 rhs: Box::new(rhs),
             })
@@ -81,6 +85,9 @@ fn parse_exp(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, PestError<Ru
         | Rule::Boolean
         | Rule::Decimal
         | Rule::Date
+        | Rule::Month
+        | Rule::Quarter
+        | Rule::Week
         | Rule::Time
         | Rule::Datetime
         | Rule::Timestamp
@@ -88,8 +95,9 @@ fn parse_exp(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, PestError<Ru
         | Rule::CatAlt
         | Rule::Cat
         | Rule::String
-        | Rule::Null => parse_j(pair),
-        Rule::Series => parse_series(pair),
+        | Rule::Null => parse_j(pair, &opts.timezone),
+        Rule::TypedNumber => parse_typed_number(pair),
+        Rule::Series => parse_series(pair, &opts.timezone),
         Rule::Cats => parse_cats(pair),
         Rule::AssignmentExp => {
             let mut pairs = pair.into_inner();
@@ -99,9 +107,9 @@ fn parse_exp(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, PestError<Ru
                 let id = fn_call.next().unwrap();
                 let mut indices: Vec<AstNode> = Vec::with_capacity(fn_call.len() - 1);
                 for arg in fn_call {
-                    indices.push(parse_exp(arg.into_inner().next().unwrap(), source_id)?)
+                    indices.push(parse_exp(arg.into_inner().next().unwrap(), opts)?)
                 }
-                let exp = parse_exp(pairs.next().unwrap(), source_id)?;
+                let exp = parse_exp(pairs.next().unwrap(), opts)?;
                 Ok(AstNode::IndexAssign {
                     id: Box::new(AstNode::Id {
                         name: id.as_str().to_owned(),
@@ -113,7 +121,7 @@ fn parse_exp(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, PestError<Ru
                 })
             } else {
                 let exp = pairs.next().unwrap();
-                let exp = parse_exp(exp, source_id)?;
+                let exp = parse_exp(exp, opts)?;
                 if is_keyword(id.as_str()) {
                     Err(raise_error(
                         format!("Keyword cannot be used as identifier: {}", id.as_str()),
@@ -154,7 +162,7 @@ fn parse_exp(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, PestError<Ru
             let pairs = pairs.next().unwrap().into_inner();
             let mut nodes = Vec::with_capacity(pairs.len());
             for pair in pairs {
-                nodes.push(parse_exp(pair, source_id)?)
+                nodes.push(parse_exp(pair, opts)?)
             }
             Ok(AstNode::Fn {
                 stmts: nodes,
@@ -167,7 +175,7 @@ fn parse_exp(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, PestError<Ru
         Rule::FnCall => {
             let span = pair.as_span();
             let mut pairs = pair.into_inner();
-            let f = parse_exp(pairs.next().unwrap(), source_id)?;
+            let f = parse_exp(pairs.next().unwrap(), opts)?;
             let arg_len = pairs.len();
             let mut args = Vec::with_capacity(arg_len);
             for pair in pairs {
@@ -176,7 +184,7 @@ fn parse_exp(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, PestError<Ru
                     args = vec![];
                     break;
                 }
-                args.push(parse_exp(arg, source_id)?)
+                args.push(parse_exp(arg, opts)?)
             }
             // if f is eval, and first args is J::String, parse J::string
             Ok(AstNode::Call {
@@ -188,11 +196,11 @@ fn parse_exp(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, PestError<Ru
         }
         Rule::IfExp => {
             let mut pairs = pair.into_inner();
-            let cond = parse_exp(pairs.next().unwrap(), source_id)?;
+            let cond = parse_exp(pairs.next().unwrap(), opts)?;
             let mut nodes = Vec::new();
             for pair in pairs.next().unwrap().into_inner() {
                 let rule = pair.as_rule();
-                nodes.push(parse_exp(pair, source_id)?);
+                nodes.push(parse_exp(pair, opts)?);
                 if rule == Rule::ReturnExp {
                     break;
                 }
@@ -204,11 +212,11 @@ fn parse_exp(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, PestError<Ru
         }
         Rule::WhileExp => {
             let mut pairs = pair.into_inner();
-            let cond = parse_exp(pairs.next().unwrap(), source_id)?;
+            let cond = parse_exp(pairs.next().unwrap(), opts)?;
             let mut nodes = Vec::new();
             for pair in pairs.next().unwrap().into_inner() {
                 let rule = pair.as_rule();
-                nodes.push(parse_exp(pair, source_id)?);
+                nodes.push(parse_exp(pair, opts)?);
                 if rule == Rule::ReturnExp {
                     break;
                 }
@@ -223,11 +231,11 @@ fn parse_exp(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, PestError<Ru
             let mut tries = Vec::new();
             let mut catches = Vec::new();
             for pair in pairs.next().unwrap().into_inner() {
-                tries.push(parse_exp(pair, source_id)?);
+                tries.push(parse_exp(pair, opts)?);
             }
             let err = pairs.next().unwrap().as_str().to_owned();
             for pair in pairs.next().unwrap().into_inner() {
-                catches.push(parse_exp(pair, source_id)?);
+                catches.push(parse_exp(pair, opts)?);
             }
             Ok(AstNode::Try {
                 tries,
@@ -236,12 +244,12 @@ fn parse_exp(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, PestError<Ru
             })
         }
         Rule::ReturnExp => {
-            let node = parse_exp(pair.into_inner().next().unwrap(), source_id)?;
+            let node = parse_exp(pair.into_inner().next().unwrap(), opts)?;
             Ok(AstNode::Return(Box::new(node)))
         }
         Rule::RaiseExp => {
             let start = pair.as_span().start();
-            let node = parse_exp(pair.into_inner().next().unwrap(), source_id)?;
+            let node = parse_exp(pair.into_inner().next().unwrap(), opts)?;
             Ok(AstNode::Raise {
                 exp: Box::new(node),
                 start,
@@ -249,6 +257,19 @@ fn parse_exp(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, PestError<Ru
             })
         }
         Rule::Skip => Ok(AstNode::Skip),
+        Rule::Param if !opts.params => Err(raise_error(
+            "parameters are disabled".to_owned(),
+            pair.as_span(),
+        )),
+        Rule::Param => {
+            let span = pair.as_span();
+            Ok(AstNode::Param {
+                name: pair.as_str()[1..].to_owned(),
+                start: span.start(),
+                end: span.end(),
+                source_id,
+            })
+        }
         Rule::Dataframe => {
             let span = pair.as_span();
             let cols = pair.into_inner();
@@ -261,10 +282,10 @@ fn parse_exp(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, PestError<Ru
                 if node.as_rule() == Rule::RenameSeriesExp {
                     let mut nodes = node.into_inner();
                     name = nodes.next().unwrap().as_str().to_owned();
-                    exp = parse_exp(nodes.next().unwrap(), source_id)?;
+                    exp = parse_exp(nodes.next().unwrap(), opts)?;
                 } else {
                     name = format!("series{:02}", i);
-                    exp = parse_exp(node, source_id)?
+                    exp = parse_exp(node, opts)?
                 }
                 if let AstNode::J(j) = exp {
                     if let J::Series(mut s) = j {
@@ -325,7 +346,7 @@ fn parse_exp(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, PestError<Ru
                 let node = col_exp.into_inner().next().unwrap();
                 let node_span = node.as_span();
                 col_name = format!("col{:02}", i);
-                exp = parse_exp(node, source_id)?;
+                exp = parse_exp(node, opts)?;
                 if let AstNode::J(j) = exp {
                     let type_name = j.get_type_name();
                     if let J::Series(mut s) = j {
@@ -373,9 +394,64 @@ fn parse_exp(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, PestError<Ru
                 Ok(AstNode::Matrix(exps))
             }
         }
-        Rule::SqlExp => parse_sql(pair, source_id),
+        Rule::SqlExp
+        | Rule::WithExp
+        | Rule::PivotExp
+        | Rule::InsertExp
+        | Rule::SetOpExp
+        | Rule::ExplainExp
+        | Rule::SubQuery
+            if !opts.sql =>
+        {
+            Err(raise_error("sql is disabled".to_owned(), pair.as_span()))
+        }
+        Rule::SqlExp => parse_sql(pair, opts),
+        Rule::WithExp => parse_with(pair, opts),
+        Rule::PivotExp => parse_pivot(pair, opts),
+        Rule::InsertExp => parse_insert(pair, opts),
+        Rule::SetOpExp => parse_set_op(pair, opts),
+        Rule::ExplainExp => {
+            let start = pair.as_span().start();
+            let sql = parse_exp(pair.into_inner().next().unwrap(), opts)?;
+            Ok(AstNode::Explain {
+                sql: Box::new(sql),
+                start,
+                source_id,
+            })
+        }
+        Rule::SubQuery => {
+            let span = pair.as_span();
+            let sql = parse_sql(pair.into_inner().next().unwrap(), opts)?;
+            check_single_column(&sql).map_err(|e| raise_error(e, span))?;
+            Ok(AstNode::SubQuery {
+                kind: "scalar".to_owned(),
+                exp: Box::new(AstNode::Skip),
+                sql: Box::new(sql),
+                start: span.start(),
+                source_id,
+            })
+        }
+        Rule::InSubQueryExp => {
+            let span = pair.as_span();
+            let mut pairs = pair.into_inner();
+            let exp = parse_exp(pairs.next().unwrap(), opts)?;
+            let kind = if pairs.next().unwrap().as_str().starts_with("not") {
+                "not in"
+            } else {
+                "in"
+            };
+            let sql = parse_sql(pairs.next().unwrap().into_inner().next().unwrap(), opts)?;
+            check_single_column(&sql).map_err(|e| raise_error(e, span))?;
+            Ok(AstNode::SubQuery {
+                kind: kind.to_owned(),
+                exp: Box::new(exp),
+                sql: Box::new(sql),
+                start: span.start(),
+                source_id,
+            })
+        }
         Rule::BracketExp | Rule::BracketSqlExp => {
-            Ok(parse_exp(pair.into_inner().next().unwrap(), source_id)?)
+            Ok(parse_exp(pair.into_inner().next().unwrap(), opts)?)
         }
         Rule::List => {
             let pair_clone = pair.clone();
@@ -407,15 +483,18 @@ fn parse_exp(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, PestError<Ru
                     | Rule::Datetime
                     | Rule::Duration
                     | Rule::Date
+                    | Rule::Month
+                    | Rule::Quarter
+                    | Rule::Week
                     | Rule::Time
                     | Rule::Decimal
                     | Rule::String
-                    | Rule::Integer => return parse_series(pair_clone),
+                    | Rule::Integer => return parse_series(pair_clone, &opts.timezone),
                     _ => {}
                 }
             }
             for pair in pairs {
-                let ast = parse_list(pair, source_id)?;
+                let ast = parse_list(pair, opts)?;
                 if let AstNode::J(_) = &ast {
                 } else {
                     all_j = false
@@ -444,7 +523,7 @@ fn parse_exp(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, PestError<Ru
                     _ => &key_node.as_str()[1..key_node.as_str().len() - 1],
                 };
                 keys.push(key.to_string());
-                let value = parse_exp(kv.next().unwrap(), source_id)?;
+                let value = parse_exp(kv.next().unwrap(), opts)?;
                 if let AstNode::J(_) = &value {
                 } else {
                     all_j = false
@@ -468,14 +547,15 @@ fn parse_exp(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, PestError<Ru
     }
 }
 
-fn parse_list(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, PestError<Rule>> {
+fn parse_list(pair: Pair<Rule>, opts: &ParseOptions) -> Result<AstNode, PestError<Rule>> {
+    let source_id = opts.source_id;
     match pair.as_rule() {
         Rule::BinaryOp => Ok(AstNode::Op {
             name: pair.as_str().to_owned(),
             start: pair.as_span().start(),
             source_id,
         }),
-        _ => parse_exp(pair, source_id),
+        _ => parse_exp(pair, opts),
         // _ => Err(raise_error(
         //     format!("Unexpected rule in list expression: {:?}", pair.as_str()),
         //     pair.as_span(),
@@ -483,25 +563,71 @@ fn parse_list(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, PestError<R
     }
 }
 
+// parses decimal, hex '0x' and binary '0b' integers, digits may be separated by '_'
+fn parse_int(s: &str, ty: &str, min: i128, max: i128) -> Result<i128, String> {
+    let out_of_range = || format!("'{}' is out of range for {}, [{}, {}]", s, ty, min, max);
+    let (sign, digits) = match s.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", s),
+    };
+    let digits = digits.replace('_', "");
+    let (radix, digits) = if let Some(digits) = digits.strip_prefix("0x") {
+        (16, digits)
+    } else if let Some(digits) = digits.strip_prefix("0b") {
+        (2, digits)
+    } else {
+        (10, digits.as_str())
+    };
+    let n = i128::from_str_radix(&format!("{}{}", sign, digits), radix).map_err(|e| {
+        match e.kind() {
+            IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => out_of_range(),
+            _ => format!("'{}': {}", s, e),
+        }
+    })?;
+    if n < min || n > max {
+        return Err(out_of_range());
+    }
+    Ok(n)
+}
+
+fn parse_float<T: FromStr<Err = ParseFloatError>>(s: &str) -> Result<T, String> {
+    let digits = s.trim_start_matches('-');
+    if digits.starts_with("0x") || digits.starts_with("0b") {
+        let n = parse_int(s, "f64", i128::MIN, i128::MAX)?;
+        return n
+            .to_string()
+            .parse::<T>()
+            .map_err(|e| format!("'{}': {}", s, e));
+    }
+    s.replace('_', "")
+        .parse::<T>()
+        .map_err(|e| format!("'{}': {}", s, e))
+}
+
 macro_rules! impl_parse_num {
     ($fn_name:ident, $ty_str:literal, $ty:ty) => {
+        impl_parse_num!($fn_name, $ty_str, $ty, |s| parse_int(
+            s,
+            $ty_str,
+            <$ty>::MIN as i128,
+            <$ty>::MAX as i128
+        )
+        .map(|n| n as $ty));
+    };
+    ($fn_name:ident, $ty_str:literal, $ty:ty, $parse:expr) => {
         fn $fn_name(unknowns: Vec<&str>, span: Span) -> Result<AstNode, PestError<Rule>> {
             match unknowns
                 .iter()
                 .map(|s| {
+                    if is_null(s) {
+                        return Ok(None);
+                    }
                     let s = if s.ends_with($ty_str) {
                         &s[..s.len() - $ty_str.len()]
                     } else {
                         s
                     };
-                    if s.is_empty() || s == "null" || s == "0n" {
-                        return Ok(None);
-                    } else {
-                        return s
-                            .parse::<$ty>()
-                            .map(|n| Some(n))
-                            .map_err(|e| format!("'{}': {}", s, e).to_string());
-                    }
+                    $parse(s).map(|n| Some(n))
                 })
                 .collect::<Result<Vec<Option<$ty>>, String>>()
             {
@@ -520,16 +646,114 @@ impl_parse_num!(parse_u32, "u32", u32);
 impl_parse_num!(parse_i32, "i32", i32);
 impl_parse_num!(parse_u64, "u64", u64);
 impl_parse_num!(parse_i64, "i64", i64);
-impl_parse_num!(parse_f32, "f32", f32);
-impl_parse_num!(parse_f64, "f64", f64);
+impl_parse_num!(parse_f32, "f32", f32, parse_float::<f32>);
+impl_parse_num!(parse_f64, "f64", f64, parse_float::<f64>);
 
-fn parse_series(pair: Pair<Rule>) -> Result<AstNode, PestError<Rule>> {
-    let mut first_scalar = "";
+// a series takes the timezone of its first value
+fn series_timezone(values: &[Option<(i64, String)>], timezone: &str) -> String {
+    values
+        .iter()
+        .flatten()
+        .next()
+        .map_or(timezone, |(_, tz)| tz)
+        .to_owned()
+}
+
+fn is_null(s: &str) -> bool {
+    s.is_empty() || s == "null" || s == "0n" || s.starts_with("0N")
+}
+
+fn null_type(s: &str, timezone: &str) -> Option<PolarsDataType> {
+    let timezone = || timezone.into();
+    let dtype = match s.strip_prefix("0N")? {
+        "u8" => PolarsDataType::UInt8,
+        "u16" => PolarsDataType::UInt16,
+        "u32" => PolarsDataType::UInt32,
+        "u64" => PolarsDataType::UInt64,
+        "i8" => PolarsDataType::Int8,
+        "i16" => PolarsDataType::Int16,
+        "i32" => PolarsDataType::Int32,
+        "i64" => PolarsDataType::Int64,
+        "f32" => PolarsDataType::Float32,
+        "f64" => PolarsDataType::Float64,
+        "str" => PolarsDataType::String,
+        "b" => PolarsDataType::Boolean,
+        "d" => PolarsDataType::Date,
+        "t" => PolarsDataType::Time,
+        "z" => PolarsDataType::Datetime(TimeUnit::Milliseconds, Some(timezone())),
+        "p" => PolarsDataType::Datetime(TimeUnit::Nanoseconds, Some(timezone())),
+        "n" => PolarsDataType::Duration(TimeUnit::Nanoseconds),
+        "s" => PolarsDataType::Categorical(None, CategoricalOrdering::Lexical),
+        _ => return None,
+    };
+    Some(dtype)
+}
+
+// typed nulls decide the data type of a series without values, and have to match it otherwise
+fn parse_series(pair: Pair<Rule>, timezone: &str) -> Result<AstNode, PestError<Rule>> {
     let span = pair.as_span();
     let unknowns: Vec<&str> = pair.into_inner().map(|p| p.as_str()).collect();
+    let nulls: Vec<(&str, PolarsDataType)> = unknowns
+        .iter()
+        .filter_map(|s| null_type(s, timezone).map(|dtype| (*s, dtype)))
+        .collect();
+    let node = match nulls.first() {
+        Some((_, dtype)) if unknowns.iter().all(|s| is_null(s)) => AstNode::J(J::Series(
+            Series::full_null("".into(), unknowns.len(), dtype),
+        )),
+        _ => parse_scalars(unknowns, span, timezone)?,
+    };
+    if let AstNode::J(J::Series(s)) = &node {
+        if let Some((null, dtype)) = nulls.iter().find(|(_, dtype)| dtype != s.dtype()) {
+            return Err(raise_error(
+                format!(
+                    "'{}' is a null of {}, got a series of {}",
+                    null,
+                    dtype,
+                    s.dtype()
+                ),
+                span,
+            ));
+        }
+    }
+    Ok(node)
+}
+
+// indices of patterns of scalars in a series literal, in the order of the regex set
+const SERIES_BOOL: usize = 0;
+const SERIES_U8: usize = 1;
+const SERIES_I8: usize = 2;
+const SERIES_U16: usize = 3;
+const SERIES_I16: usize = 4;
+const SERIES_U32: usize = 5;
+const SERIES_I32: usize = 6;
+const SERIES_U64: usize = 7;
+const SERIES_I64: usize = 8;
+const SERIES_F32: usize = 9;
+const SERIES_F64: usize = 10;
+const SERIES_DATE: usize = 11;
+const SERIES_TIME: usize = 12;
+const SERIES_DATETIME: usize = 13;
+const SERIES_TIMESTAMP: usize = 14;
+const SERIES_DURATION: usize = 15;
+const SERIES_CALENDAR_DURATION: usize = 16;
+const SERIES_QUOTED_CAT: usize = 17;
+const SERIES_STRING: usize = 18;
+const SERIES_NULL: usize = 19;
+const SERIES_CAT: usize = 20;
+const SERIES_MONTH: usize = 21;
+const SERIES_QUARTER: usize = 22;
+const SERIES_WEEK: usize = 23;
+
+fn parse_scalars(
+    unknowns: Vec<&str>,
+    span: Span,
+    timezone: &str,
+) -> Result<AstNode, PestError<Rule>> {
+    let mut first_scalar = "";
     let len = unknowns.len();
     for scalar in unknowns.iter() {
-        if !scalar.is_empty() && *scalar != "null" && *scalar != "0n" {
+        if !is_null(scalar) {
             first_scalar = scalar;
             break;
         }
@@ -540,36 +764,59 @@ fn parse_series(pair: Pair<Rule>) -> Result<AstNode, PestError<Rule>> {
             &PolarsDataType::Null,
         ))));
     }
+    // unsigned integers may be negative here to report them as out of range
+    let int = r"-?(0x[0-9a-fA-F](_?[0-9a-fA-F])*|0b[01](_?[01])*|[0-9](_?[0-9])*)";
+    let zone = r"(Z|[+-]\d{2}:\d{2}|@[A-Za-z0-9/_+-]+)?";
+    let float = r"-?(([0-9](_?[0-9])*([.][0-9]*)?|[.][0-9]+)([eE][+-]?[0-9]+)?|inf|nan)";
     let set = RegexSet::new(&[
-        r"^(true|false|1b|0b)$",
-        r"^\d+u8$",
-        r"^-?\d+i8$",
-        r"^\d+u16$",
-        r"^-?\d+i16$",
-        r"^\d+u32$",
-        r"^-?\d+i32$",
-        r"^\d+u64$",
-        r"^-?\d+(i64)?$",
-        r"^-?([0-9]+([.][0-9]*)?|[.][0-9]+)f32$",
-        r"^-?([0-9]+([.][0-9]*)?|[.][0-9]+)(f64)?$",
-        r"^\d{4}-\d{2}-\d{2}$",
-        r"^\d{2}:\d{2}:\d{2}\.\d{0,9}$",
-        r"^\d{4}-\d{2}-\d{2}T(\d{2}:\d{2}:\d{2}(\.\d{0,3})?)?$",
-        r"^\d{4}-\d{2}-\d{2}D(\d{2}:\d{2}:\d{2}(\.\d{0,9})?)?$",
-        r"^-?\d+D(\d{2}:\d{2}:\d{2}(\.\d{0,9})?)?$",
-        r"^-?\d+(ns|s|m|h)$",
-        r"^'[^']*'$",
-        r#"^"[^"]*"$"#,
-        r"(^(null|0n)$|^$)",
-        r"^`.*$",
+        r"^(true|false|1b|0b)$".to_owned(),
+        format!("^{}u8$", int),
+        format!("^{}i8$", int),
+        format!("^{}u16$", int),
+        format!("^{}i16$", int),
+        format!("^{}u32$", int),
+        format!("^{}i32$", int),
+        format!("^{}u64$", int),
+        format!("^{}(i64)?$", int),
+        format!("^({}|{})f32$", int, float),
+        format!("^({}|{})(f64)?$", int, float),
+        r"^\d{4}-\d{2}-\d{2}$".to_owned(),
+        r"^\d{2}:\d{2}:\d{2}\.\d{0,9}$".to_owned(),
+        format!(
+            r"^\d{{4}}-\d{{2}}-\d{{2}}T(\d{{2}}:\d{{2}}:\d{{2}}(\.\d{{0,3}})?)?{}$",
+            zone
+        ),
+        format!(
+            r"^\d{{4}}-\d{{2}}-\d{{2}}D(\d{{2}}:\d{{2}}:\d{{2}}(\.\d{{0,9}})?)?{}$",
+            zone
+        ),
+        r"^-?\d+D(\d{2}:\d{2}:\d{2}(\.\d{0,9})?)?$".to_owned(),
+        r"^-?\d+(ns|us|ms|s|m|h|d|w|mo|q|y)$".to_owned(),
+        r"^'[^']*'$".to_owned(),
+        r#"^"[^"]*"$"#.to_owned(),
+        r"(^(null|0n)$|^$)".to_owned(),
+        r"^`.*$".to_owned(),
+        r"^\d{4}-\d{2}m$".to_owned(),
+        r"^\d{4}Q\d$".to_owned(),
+        r"^\d{4}W\d{2}$".to_owned(),
     ])
     .unwrap();
+    debug_assert_eq!(set.len(), SERIES_WEEK + 1);
 
     let matches: Vec<_> = set.matches(&first_scalar).into_iter().collect();
-    let first_match = matches.first().copied().unwrap_or(set.len());
+    let mut first_match = matches.first().copied().unwrap_or(set.len());
+    // integers without suffix become f64 if any of the others is a float, e.g. '1 2.5e2'
+    if first_match == SERIES_I64
+        && unknowns.iter().any(|s| {
+            let matches = set.matches(s);
+            (matches.matched(SERIES_F64) && !matches.matched(SERIES_I64)) || *s == "0Nf64"
+        })
+    {
+        first_match = SERIES_F64;
+    }
 
     match first_match {
-        0 => {
+        SERIES_BOOL => {
             let mut bools = Vec::with_capacity(len);
             for bool in unknowns.iter() {
                 match *bool {
@@ -577,7 +824,7 @@ fn parse_series(pair: Pair<Rule>) -> Result<AstNode, PestError<Rule>> {
                     "1b" => bools.push(Some(true)),
                     "false" => bools.push(Some(false)),
                     "0b" => bools.push(Some(false)),
-                    "null" | "0n" | "" => bools.push(None),
+                    s if is_null(s) => bools.push(None),
                     _ => {
                         return Err(raise_error(
                             format!("unrecognized bool value {}", bool),
@@ -589,21 +836,21 @@ fn parse_series(pair: Pair<Rule>) -> Result<AstNode, PestError<Rule>> {
             let s = Series::new("".into(), bools);
             Ok(AstNode::J(J::Series(s)))
         }
-        1 => parse_u8(unknowns, span),
-        2 => parse_i8(unknowns, span),
-        3 => parse_u16(unknowns, span),
-        4 => parse_i16(unknowns, span),
-        5 => parse_u32(unknowns, span),
-        6 => parse_i32(unknowns, span),
-        7 => parse_u64(unknowns, span),
-        8 => parse_i64(unknowns, span),
-        9 => parse_f32(unknowns, span),
-        10 => parse_f64(unknowns, span),
-        11 => {
+        SERIES_U8 => parse_u8(unknowns, span),
+        SERIES_I8 => parse_i8(unknowns, span),
+        SERIES_U16 => parse_u16(unknowns, span),
+        SERIES_I16 => parse_i16(unknowns, span),
+        SERIES_U32 => parse_u32(unknowns, span),
+        SERIES_I32 => parse_i32(unknowns, span),
+        SERIES_U64 => parse_u64(unknowns, span),
+        SERIES_I64 => parse_i64(unknowns, span),
+        SERIES_F32 => parse_f32(unknowns, span),
+        SERIES_F64 => parse_f64(unknowns, span),
+        SERIES_DATE => {
             let dates = unknowns
                 .iter()
                 .map(|s| {
-                    if *s == "" || *s == "null" || *s == "0n" {
+                    if is_null(s) {
                         Ok(None)
                     } else {
                         parse_date(s)
@@ -618,11 +865,11 @@ fn parse_series(pair: Pair<Rule>) -> Result<AstNode, PestError<Rule>> {
                     .map_err(|e| raise_error(e.to_string(), span))?,
             )))
         }
-        12 => {
+        SERIES_TIME => {
             let times = unknowns
                 .iter()
                 .map(|s| {
-                    if *s == "" || *s == "null" || *s == "0n" {
+                    if is_null(s) {
                         Ok(None)
                     } else {
                         parse_time(*s)
@@ -637,84 +884,107 @@ fn parse_series(pair: Pair<Rule>) -> Result<AstNode, PestError<Rule>> {
                     .map_err(|e| raise_error(e.to_string(), span))?,
             )))
         }
-        13 => {
+        SERIES_DATETIME => {
             let datetimes = unknowns
                 .iter()
                 .map(|s| {
-                    if *s == "" || *s == "null" || *s == "0n" {
+                    if is_null(s) {
                         Ok(None)
                     } else {
-                        parse_datetime(*s)
+                        parse_datetime(s, timezone)
                             .map_err(|e| raise_error(format!("'{}': {}", s, e), span))
                             .map(|dt| Some(dt))
                     }
                 })
                 .collect::<Result<Vec<_>, _>>()?;
+            let timezone = series_timezone(&datetimes, timezone);
             Ok(AstNode::J(J::Series(
-                Series::new("".into(), datetimes)
-                    .cast(&PolarsDataType::Datetime(
-                        TimeUnit::Milliseconds,
-                        Some(
-                            iana_time_zone::get_timezone()
-                                .unwrap_or("UTC".to_owned())
-                                .into(),
-                        ),
-                    ))
-                    .map_err(|e| raise_error(e.to_string(), span))?,
+                Series::new(
+                    "".into(),
+                    datetimes
+                        .into_iter()
+                        .map(|dt| dt.map(|dt| dt.0))
+                        .collect::<Vec<_>>(),
+                )
+                .cast(&PolarsDataType::Datetime(
+                    TimeUnit::Milliseconds,
+                    Some(timezone.into()),
+                ))
+                .map_err(|e| raise_error(e.to_string(), span))?,
             )))
         }
-        14 => {
+        SERIES_TIMESTAMP => {
             let timestamps = unknowns
                 .iter()
                 .map(|s| {
-                    if *s == "" || *s == "null" || *s == "0n" {
+                    if is_null(s) {
                         Ok(None)
                     } else {
-                        parse_timestamp(*s)
+                        parse_timestamp(s, timezone)
                             .map_err(|e| raise_error(format!("'{}': {}", s, e), span))
                             .map(|ts| Some(ts))
                     }
                 })
                 .collect::<Result<Vec<_>, _>>()?;
+            let timezone = series_timezone(&timestamps, timezone);
             Ok(AstNode::J(J::Series(
-                Series::new("".into(), timestamps)
-                    .cast(&PolarsDataType::Datetime(
-                        TimeUnit::Nanoseconds,
-                        Some(
-                            iana_time_zone::get_timezone()
-                                .unwrap_or("UTC".to_owned())
-                                .into(),
-                        ),
-                    ))
-                    .map_err(|e| raise_error(e.to_string(), span))?,
+                Series::new(
+                    "".into(),
+                    timestamps
+                        .into_iter()
+                        .map(|ts| ts.map(|ts| ts.0))
+                        .collect::<Vec<_>>(),
+                )
+                .cast(&PolarsDataType::Datetime(
+                    TimeUnit::Nanoseconds,
+                    Some(timezone.into()),
+                ))
+                .map_err(|e| raise_error(e.to_string(), span))?,
             )))
         }
-        15 | 16 => {
+        SERIES_DURATION | SERIES_CALENDAR_DURATION => {
             let times = unknowns
                 .iter()
                 .map(|s| {
-                    if *s == "" || *s == "null" || *s == "0n" {
-                        Ok(None)
-                    } else {
-                        parse_duration(*s)
-                            .map_err(|e| raise_error(format!("'{}': {}", s, e), span))
-                            .map(|t| Some(t))
+                    if is_null(s) {
+                        return Ok(None);
+                    }
+                    match parse_duration(s) {
+                        Ok(ParsedDuration::Fixed(ns)) => Ok(Some((0, 0, ns))),
+                        Ok(ParsedDuration::Calendar { months, days }) => {
+                            Ok(Some((months, days, 0)))
+                        }
+                        Err(e) => Err(raise_error(format!("'{}': {}", s, e), span)),
                     }
                 })
                 .collect::<Result<Vec<_>, _>>()?;
+            // a series of any calendar duration is a series of periods
+            if times
+                .iter()
+                .flatten()
+                .any(|(months, days, _)| *months != 0 || *days != 0)
+            {
+                return period_series(&times)
+                    .map(|s| AstNode::J(J::Series(s)))
+                    .map_err(|e| raise_error(e, span));
+            }
+            let times = times
+                .into_iter()
+                .map(|t| t.map(|t| t.2))
+                .collect::<Vec<_>>();
             Ok(AstNode::J(J::Series(
                 Series::new("".into(), times)
                     .cast(&PolarsDataType::Duration(TimeUnit::Nanoseconds))
                     .map_err(|e| raise_error(e.to_string(), span))?,
             )))
         }
-        17 => {
+        SERIES_QUOTED_CAT => {
             let cats = unknowns
                 .iter()
                 .map(|s| {
                     if Regex::new(r"^'[^']*'$").unwrap().is_match(s.as_bytes()) {
                         Ok(Some(s[1..s.len() - 1].to_owned()))
-                    } else if *s == "" || *s == "null" || *s == "0n" {
+                    } else if is_null(s) {
                         Ok(None)
                     } else {
                         Err(raise_error(
@@ -733,13 +1003,13 @@ fn parse_series(pair: Pair<Rule>) -> Result<AstNode, PestError<Rule>> {
                     .map_err(|e| raise_error(e.to_string(), span))?,
             )))
         }
-        18 => {
+        SERIES_STRING => {
             let strings = unknowns
                 .iter()
                 .map(|s| {
                     if Regex::new(r#"^"[^"]*"$"#).unwrap().is_match(s.as_bytes()) {
                         Ok(Some(s[1..s.len() - 1].to_owned()))
-                    } else if *s == "" || *s == "null" || *s == "0n" {
+                    } else if is_null(s) {
                         Ok(None)
                     } else {
                         Err(raise_error(format!("'{}': {}", s, "not a string"), span))
@@ -748,14 +1018,14 @@ fn parse_series(pair: Pair<Rule>) -> Result<AstNode, PestError<Rule>> {
                 .collect::<Result<Vec<_>, _>>()?;
             Ok(AstNode::J(J::Series(Series::new("".into(), strings))))
         }
-        19 => Ok(AstNode::J(J::Series(Series::new_null("".into(), len)))),
-        20 => {
+        SERIES_NULL => Ok(AstNode::J(J::Series(Series::new_null("".into(), len)))),
+        SERIES_CAT => {
             let cats = unknowns
                 .iter()
                 .map(|s| {
                     if Regex::new(r"^`.*$").unwrap().is_match(s.as_bytes()) {
                         Ok(Some(s[1..].to_owned()))
-                    } else if *s == "" || *s == "null" || *s == "0n" {
+                    } else if is_null(s) {
                         Ok(None)
                     } else {
                         Err(raise_error(
@@ -774,22 +1044,64 @@ fn parse_series(pair: Pair<Rule>) -> Result<AstNode, PestError<Rule>> {
                     .map_err(|e| raise_error(e.to_string(), span))?,
             )))
         }
+        // series of months, quarters and weeks are dates of their first days
+        SERIES_MONTH | SERIES_QUARTER | SERIES_WEEK => {
+            let dates = unknowns
+                .iter()
+                .map(|s| {
+                    if is_null(s) {
+                        Ok(None)
+                    } else {
+                        parse_calendar(s)
+                            .map(|j| j.first_day())
+                            .map_err(|e| raise_error(format!("'{}': {}", s, e), span))
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(AstNode::J(J::Series(
+                Series::new("".into(), dates)
+                    .cast(&PolarsDataType::Date)
+                    .map_err(|e| raise_error(e.to_string(), span))?,
+            )))
+        }
         _ => Err(raise_error("unknown series".to_owned(), span)),
     }
 }
 
-fn parse_j(pair: Pair<Rule>) -> Result<AstNode, PestError<Rule>> {
+fn parse_typed_number(pair: Pair<Rule>) -> Result<AstNode, PestError<Rule>> {
+    let s = pair.as_str();
+    let suffix_len = if s.ends_with("u8") || s.ends_with("i8") {
+        2
+    } else {
+        3
+    };
+    let (n, suffix) = s.split_at(s.len() - suffix_len);
+    let j = match suffix {
+        "i64" => parse_int(n, "i64", i64::MIN as i128, i64::MAX as i128).map(|n| J::I64(n as i64)),
+        "f64" => parse_float::<f64>(n).map(J::F64),
+        _ => Err(format!(
+            "'{}': scalars are i64 or f64, '{}' is only supported in series literals, e.g. '{} 0'",
+            s, suffix, s
+        )),
+    };
+    j.map(AstNode::J)
+        .map_err(|e| raise_error(e, pair.as_span()))
+}
+
+fn parse_j(pair: Pair<Rule>, timezone: &str) -> Result<AstNode, PestError<Rule>> {
     match pair.as_rule() {
         Rule::Boolean => Ok(AstNode::J(J::Boolean(
             pair.as_str() == "1b" || pair.as_str() == "true",
         ))),
-        Rule::Integer => match pair.as_str().parse::<i64>() {
-            Ok(n) => Ok(AstNode::J(J::I64(n))),
-            Err(e) => Err(raise_error(e.to_string(), pair.as_span())),
-        },
-        Rule::Decimal => match pair.as_str().parse::<f64>() {
+        Rule::Integer => {
+            match parse_int(pair.as_str(), "i64", i64::MIN as i128, i64::MAX as i128) {
+                Ok(n) => Ok(AstNode::J(J::I64(n as i64))),
+                Err(e) => Err(raise_error(e, pair.as_span())),
+            }
+        }
+        Rule::Decimal => match parse_float::<f64>(pair.as_str()) {
             Ok(n) => Ok(AstNode::J(J::F64(n))),
-            Err(e) => Err(raise_error(e.to_string(), pair.as_span())),
+            Err(e) => Err(raise_error(e, pair.as_span())),
         },
         Rule::Date => {
             let j = parse_date(pair.as_str())
@@ -797,6 +1109,9 @@ fn parse_j(pair: Pair<Rule>) -> Result<AstNode, PestError<Rule>> {
                 .map(|j| J::Date(j - UNIX_EPOCH_DAY))?;
             Ok(AstNode::J(j))
         }
+        Rule::Month | Rule::Quarter | Rule::Week => Ok(AstNode::J(
+            parse_calendar(pair.as_str()).map_err(|e| raise_error(e, pair.as_span()))?,
+        )),
         Rule::Time => {
             let j = parse_time(pair.as_str())
                 .map_err(|e| raise_error(e.to_string(), pair.as_span()))
@@ -804,28 +1119,21 @@ fn parse_j(pair: Pair<Rule>) -> Result<AstNode, PestError<Rule>> {
             Ok(AstNode::J(j))
         }
         Rule::Datetime => {
-            let j = parse_datetime(pair.as_str())
+            let j = parse_datetime(pair.as_str(), timezone)
                 .map_err(|e| raise_error(e.to_string(), pair.as_span()))
-                .map(|j| J::Datetime {
-                    ms: j,
-                    timezone: iana_time_zone::get_timezone().unwrap_or("UTC".to_owned()),
-                })?;
+                .map(|(ms, timezone)| J::Datetime { ms, timezone })?;
             Ok(AstNode::J(j))
         }
         Rule::Timestamp => {
-            let j = parse_timestamp(pair.as_str())
+            let j = parse_timestamp(pair.as_str(), timezone)
                 .map_err(|e| raise_error(e.to_string(), pair.as_span()))
-                .map(|j| J::Timestamp {
-                    ns: j,
-                    timezone: iana_time_zone::get_timezone().unwrap_or("UTC".to_owned()),
-                })?;
+                .map(|(ns, timezone)| J::Timestamp { ns, timezone })?;
             Ok(AstNode::J(j))
         }
         Rule::Duration => {
-            let j = parse_duration(pair.as_str())
-                .map_err(|e| raise_error(e.to_string(), pair.as_span()))
-                .map(|j| J::Duration(j))?;
-            Ok(AstNode::J(j))
+            let duration = parse_duration(pair.as_str())
+                .map_err(|e| raise_error(e.to_string(), pair.as_span()))?;
+            Ok(AstNode::J(duration.into_j()))
         }
         Rule::Cat => Ok(AstNode::J(J::Cat(pair.as_str()[1..].to_string()))),
         Rule::CatAlt => Ok(AstNode::J(J::Cat(
@@ -838,7 +1146,10 @@ fn parse_j(pair: Pair<Rule>) -> Result<AstNode, PestError<Rule>> {
             // Escaped string quotes become single quotes here.
             Ok(AstNode::J(J::String(str.to_owned())))
         }
-        Rule::Null => Ok(AstNode::J(J::Null)),
+        Rule::Null => match null_type(pair.as_str(), timezone) {
+            Some(dtype) => Ok(AstNode::J(J::TypedNull(dtype))),
+            None => Ok(AstNode::J(J::Null)),
+        },
         unexpected_exp => Err(raise_error(
             format!("Unexpected j: {:?}", unexpected_exp),
             pair.as_span(),
@@ -858,7 +1169,8 @@ fn parse_cats(pair: Pair<Rule>) -> Result<AstNode, PestError<Rule>> {
     )))
 }
 
-fn parse_sql(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, PestError<Rule>> {
+fn parse_sql(pair: Pair<Rule>, opts: &ParseOptions) -> Result<AstNode, PestError<Rule>> {
+    let source_id = opts.source_id;
     let span = pair.as_span();
     let mut pairs = pair.into_inner();
     // select, update, exec, delete
@@ -869,6 +1181,9 @@ fn parse_sql(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, PestError<Ru
     let mut filters: Vec<AstNode> = Vec::new();
     let mut sorts: Vec<AstNode> = Vec::new();
     let mut take = AstNode::J(J::Null);
+    let mut take_by: Vec<AstNode> = Vec::new();
+    let mut skip = AstNode::J(J::Null);
+    let mut distinct = false;
     let mut group_type = "by";
     while let Some(some_pair) = pairs.next() {
         match some_pair.as_rule() {
@@ -876,7 +1191,11 @@ fn parse_sql(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, PestError<Ru
                 op = &some_pair.as_str()[..6];
                 let op_pairs = some_pair.into_inner();
                 for op_pair in op_pairs {
-                    ops.push(parse_sql_col_exp(op_pair, source_id)?)
+                    if op_pair.as_rule() == Rule::Distinct {
+                        distinct = true;
+                    } else {
+                        ops.push(parse_sql_col_exp(op_pair, opts)?)
+                    }
                 }
             }
             Rule::GroupExp => {
@@ -888,17 +1207,11 @@ fn parse_sql(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, PestError<Ru
                 let group_pairs = some_pair.into_inner();
                 groups = Vec::with_capacity(group_pairs.len());
                 for group_pair in group_pairs {
-                    groups.push(parse_sql_col_exp(group_pair, source_id)?)
-                }
-            }
-            Rule::FromExp => from = parse_exp(some_pair.into_inner().next().unwrap(), source_id)?,
-            Rule::FilterExp => {
-                let filter_pairs = some_pair.into_inner();
-                filters = Vec::with_capacity(filter_pairs.len());
-                for filter_pair in filter_pairs {
-                    filters.push(parse_exp(filter_pair, source_id)?)
+                    groups.push(parse_sql_col_exp(group_pair, opts)?)
                 }
             }
+            Rule::FromExp => from = parse_from(some_pair, opts)?,
+            Rule::FilterExp => filters = parse_filters(some_pair, opts)?,
             Rule::SortOp => {
                 let sort_pairs = some_pair.into_inner();
                 sorts = Vec::with_capacity(sort_pairs.len());
@@ -910,7 +1223,16 @@ fn parse_sql(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, PestError<Ru
                     })
                 }
             }
-            Rule::TakeOp => take = parse_exp(some_pair.into_inner().next().unwrap(), source_id)?,
+            Rule::TakeOp => {
+                let mut take_pairs = some_pair.into_inner();
+                take = parse_exp(take_pairs.next().unwrap(), opts)?;
+                if let Some(by_pair) = take_pairs.next() {
+                    for by_pair in by_pair.into_inner() {
+                        take_by.push(parse_sql_col_exp(by_pair, opts)?)
+                    }
+                }
+            }
+            Rule::SkipOp => skip = parse_exp(some_pair.into_inner().nth(1).unwrap(), opts)?,
             unexpected_exp => {
                 return Err(raise_error(
                     format!("Unexpected sql: {:?}", unexpected_exp),
@@ -928,18 +1250,448 @@ fn parse_sql(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, PestError<Ru
         filters,
         sorts,
         take: Box::new(take),
+        take_by,
+        skip: Box::new(skip),
+        distinct,
         source_id,
         start: span.start(),
     })
 }
 
-fn parse_sql_col_exp(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, PestError<Rule>> {
+fn parse_from(pair: Pair<Rule>, opts: &ParseOptions) -> Result<AstNode, PestError<Rule>> {
+    parse_table(pair.into_inner().next().unwrap(), opts)
+}
+
+fn parse_table(pair: Pair<Rule>, opts: &ParseOptions) -> Result<AstNode, PestError<Rule>> {
+    if pair.as_rule() == Rule::SubQuery {
+        parse_sql(pair.into_inner().next().unwrap(), opts)
+    } else {
+        parse_exp(pair, opts)
+    }
+}
+
+fn parse_insert(pair: Pair<Rule>, opts: &ParseOptions) -> Result<AstNode, PestError<Rule>> {
+    let source_id = opts.source_id;
+    let span = pair.as_span();
+    let mut pairs = pair.into_inner();
+    let kind = pairs.next().unwrap().as_str();
+    let table = pairs.next().unwrap().as_str().to_owned();
+    let mut keys: Vec<(String, Span)> = Vec::new();
+    let mut exp_pair = pairs.next().unwrap();
+    if exp_pair.as_rule() == Rule::InsertKeys {
+        for key_pair in exp_pair.into_inner() {
+            let key = key_pair.as_str().to_owned();
+            if keys.iter().any(|(k, _)| *k == key) {
+                return Err(raise_error(
+                    format!("duplicate key column: {}", key),
+                    key_pair.as_span(),
+                ));
+            }
+            keys.push((key, key_pair.as_span()));
+        }
+        exp_pair = pairs.next().unwrap();
+    }
+    match (kind, keys.is_empty()) {
+        ("upsert", true) => {
+            return Err(raise_error(
+                "'upsert' requires key columns by 'on'".to_owned(),
+                span,
+            ))
+        }
+        ("insert", false) => {
+            return Err(raise_error(
+                "'insert' does not take key columns, use 'upsert' instead".to_owned(),
+                keys[0].1,
+            ))
+        }
+        _ => (),
+    }
+    // column names of a literal dataframe have to be provided, and include all key columns
+    if exp_pair.as_rule() == Rule::Dataframe {
+        let mut names: Vec<String> = Vec::new();
+        for col_pair in exp_pair.clone().into_inner() {
+            let col_span = col_pair.as_span();
+            let node = col_pair.into_inner().next().unwrap();
+            let name = if node.as_rule() == Rule::RenameSeriesExp {
+                node.into_inner().next().unwrap().as_str()
+            } else {
+                let name = node.as_str();
+                if !name.starts_with(|c: char| c.is_ascii_alphabetic())
+                    || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+                {
+                    return Err(raise_error(
+                        format!("'{}' requires a column name, got '{}'", kind, name),
+                        col_span,
+                    ));
+                }
+                name
+            };
+            if names.iter().any(|n| n == name) {
+                return Err(raise_error(
+                    format!("duplicate column name: {}", name),
+                    col_span,
+                ));
+            }
+            names.push(name.to_owned())
+        }
+        for (key, key_span) in keys.iter() {
+            if !names.contains(key) {
+                return Err(raise_error(
+                    format!("key column '{}' does not exist in dataframe", key),
+                    *key_span,
+                ));
+            }
+        }
+    }
+    let exp = parse_table(exp_pair, opts)?;
+    Ok(AstNode::Insert {
+        kind: kind.to_owned(),
+        table,
+        keys: keys.into_iter().map(|(key, _)| key).collect(),
+        exp: Box::new(exp),
+        start: span.start(),
+        source_id,
+    })
+}
+
+fn parse_set_op(pair: Pair<Rule>, opts: &ParseOptions) -> Result<AstNode, PestError<Rule>> {
+    let source_id = opts.source_id;
+    let mut pairs = pair.into_inner();
+    let lhs_pair = pairs.next().unwrap();
+    // the number of columns is only known for literal dataframes, and taken from the left side
+    let width = df_width(&lhs_pair);
+    let mut operands = vec![(lhs_pair.as_span().start(), parse_exp(lhs_pair, opts)?)];
+    let mut ops = Vec::new();
+    while let Some(op_pair) = pairs.next() {
+        let op = op_pair
+            .as_str()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let rhs_pair = pairs.next().unwrap();
+        if let (Some(lhs_width), Some(rhs_width)) = (width, df_width(&rhs_pair)) {
+            if lhs_width != rhs_width {
+                return Err(raise_error(
+                    format!(
+                        "'{}' requires the same number of columns, got {} and {}",
+                        op, lhs_width, rhs_width
+                    ),
+                    rhs_pair.as_span(),
+                ));
+            }
+        }
+        operands.push((rhs_pair.as_span().start(), parse_exp(rhs_pair, opts)?));
+        ops.push(op);
+    }
+    Ok(fold_set_ops(operands, ops, source_id))
+}
+
+// 'intersect' binds tighter than 'union' and 'except', which are evaluated from left to right
+pub(crate) fn fold_set_ops(
+    operands: Vec<(usize, AstNode)>,
+    ops: Vec<String>,
+    source_id: usize,
+) -> AstNode {
+    let set_op = |op, lhs, rhs, start| AstNode::SetOp {
+        op,
+        lhs: Box::new(lhs),
+        rhs: Box::new(rhs),
+        start,
+        source_id,
+    };
+    let mut operands = operands.into_iter();
+    let mut terms = vec![operands.next().unwrap()];
+    let mut term_ops = Vec::new();
+    for (op, (start, rhs)) in ops.into_iter().zip(operands) {
+        if op == "intersect" {
+            let (lhs_start, lhs) = terms.pop().unwrap();
+            terms.push((lhs_start, set_op(op, lhs, rhs, lhs_start)));
+        } else {
+            term_ops.push(op);
+            terms.push((start, rhs));
+        }
+    }
+    let mut terms = terms.into_iter();
+    let (start, mut node) = terms.next().unwrap();
+    for (op, (_, rhs)) in term_ops.into_iter().zip(terms) {
+        node = set_op(op, node, rhs, start)
+    }
+    node
+}
+
+fn df_width(pair: &Pair<Rule>) -> Option<usize> {
+    if pair.as_rule() == Rule::Dataframe {
+        Some(pair.clone().into_inner().len())
+    } else {
+        None
+    }
+}
+
+fn parse_filters(pair: Pair<Rule>, opts: &ParseOptions) -> Result<Vec<AstNode>, PestError<Rule>> {
+    let filter_pairs = pair.into_inner();
+    let mut filters = Vec::with_capacity(filter_pairs.len());
+    for filter_pair in filter_pairs {
+        filters.push(parse_exp(filter_pair, opts)?)
+    }
+    Ok(filters)
+}
+
+fn parse_pivot(pair: Pair<Rule>, opts: &ParseOptions) -> Result<AstNode, PestError<Rule>> {
+    let source_id = opts.source_id;
+    let span = pair.as_span();
+    let mut kind = "pivot";
+    let mut values: Vec<AstNode> = Vec::new();
+    let mut index: Vec<AstNode> = Vec::new();
+    let mut name: Option<(String, AstNode)> = None;
+    let mut from: AstNode = AstNode::Skip;
+    let mut filters: Vec<AstNode> = Vec::new();
+    for some_pair in pair.into_inner() {
+        match some_pair.as_rule() {
+            Rule::PivotOp | Rule::UnpivotOp => {
+                if some_pair.as_rule() == Rule::UnpivotOp {
+                    kind = "unpivot"
+                }
+                for value_pair in some_pair.into_inner() {
+                    values.push(parse_sql_col_exp(value_pair, opts)?)
+                }
+            }
+            Rule::PivotBy => {
+                for index_pair in some_pair.into_inner() {
+                    index.push(parse_sql_col_exp(index_pair, opts)?)
+                }
+            }
+            Rule::PivotFor => {
+                let for_span = some_pair.as_span();
+                let mut names = Vec::new();
+                for name_pair in some_pair.into_inner() {
+                    names.push(parse_sql_col_exp(name_pair, opts)?)
+                }
+                match names.pop() {
+                    Some(AstNode::Id {
+                        name: on,
+                        start,
+                        source_id,
+                    }) if names.is_empty() => {
+                        let node = AstNode::Id {
+                            name: on.clone(),
+                            start,
+                            source_id,
+                        };
+                        name = Some((on, node))
+                    }
+                    _ => {
+                        return Err(raise_error(
+                            format!(
+                                "'{}' requires a single series name for 'for', got '{}'",
+                                kind,
+                                for_span.as_str()[3..].trim()
+                            ),
+                            for_span,
+                        ))
+                    }
+                }
+            }
+            Rule::FromExp => from = parse_from(some_pair, opts)?,
+            Rule::FilterExp => filters = parse_filters(some_pair, opts)?,
+            unexpected_exp => {
+                return Err(raise_error(
+                    format!("Unexpected pivot: {:?}", unexpected_exp),
+                    some_pair.as_span(),
+                ))
+            }
+        }
+    }
+    if values.is_empty() {
+        return Err(raise_error(
+            format!("'{}' requires at least one value series", kind),
+            span,
+        ));
+    }
+    let index_num = index.len();
+    let (groups, ops, name) = if kind == "pivot" {
+        let Some((on, node)) = name else {
+            return Err(raise_error("'pivot' requires 'for'".to_owned(), span));
+        };
+        if index.is_empty() {
+            return Err(raise_error("'pivot' requires 'by'".to_owned(), span));
+        }
+        index.push(node);
+        (index, values, on)
+    } else {
+        let on = name.map_or("variable".to_owned(), |(on, _)| on);
+        index.extend(values);
+        (Vec::new(), index, on)
+    };
+    // select index, 'for' and value series, then reshape its result
+    let sql = AstNode::Sql {
+        op: "select".to_owned(),
+        from: Box::new(from),
+        filters,
+        group_type: "by".to_owned(),
+        groups,
+        ops,
+        sorts: Vec::new(),
+        take: Box::new(AstNode::J(J::Null)),
+        take_by: Vec::new(),
+        skip: Box::new(AstNode::J(J::Null)),
+        distinct: false,
+        source_id,
+        start: span.start(),
+    };
+    Ok(AstNode::Pivot {
+        kind: kind.to_owned(),
+        sql: Box::new(sql),
+        index: index_num,
+        name,
+        start: span.start(),
+        source_id,
+    })
+}
+
+fn parse_with(pair: Pair<Rule>, opts: &ParseOptions) -> Result<AstNode, PestError<Rule>> {
+    let source_id = opts.source_id;
+    let span = pair.as_span();
+    let mut names: Vec<String> = Vec::new();
+    let mut sqls: Vec<AstNode> = Vec::new();
+    let mut sql = AstNode::Skip;
+    let mut cte_spans = Vec::new();
+    for some_pair in pair.into_inner() {
+        match some_pair.as_rule() {
+            Rule::CteExp => {
+                let mut pairs = some_pair.into_inner();
+                let name_pair = pairs.next().unwrap();
+                let name = name_pair.as_str().to_owned();
+                if names.contains(&name) {
+                    return Err(raise_error(
+                        format!("duplicate common table expression name: {}", name),
+                        name_pair.as_span(),
+                    ));
+                }
+                let sql_pair = pairs.next().unwrap();
+                cte_spans.push(sql_pair.as_span());
+                sqls.push(parse_sql(sql_pair, opts)?);
+                names.push(name);
+            }
+            _ => sql = parse_sql(some_pair, opts)?,
+        }
+    }
+    // a common table expression can only refer to the ones defined before it
+    for (i, cte) in sqls.iter().enumerate() {
+        let mut tables = Vec::new();
+        collect_tables(cte, &mut tables);
+        for (table, start) in tables {
+            if let Some(j) = names.iter().position(|name| *name == table) {
+                if j >= i {
+                    let span = Span::new(span.get_input(), start, start + table.len())
+                        .unwrap_or(cte_spans[i]);
+                    return Err(raise_error(
+                        format!(
+                            "common table expression '{}' cannot refer to '{}' before its definition",
+                            names[i], table
+                        ),
+                        span,
+                    ));
+                }
+            }
+        }
+    }
+    Ok(AstNode::With {
+        names,
+        sqls,
+        sql: Box::new(sql),
+        start: span.start(),
+        source_id,
+    })
+}
+
+// names of the tables referred by 'from', including the ones in subqueries
+fn collect_tables(node: &AstNode, tables: &mut Vec<(String, usize)>) {
+    match node {
+        AstNode::Sql {
+            from,
+            filters,
+            groups,
+            ops,
+            ..
+        } => {
+            if let AstNode::Id { name, start, .. } = from.as_ref() {
+                tables.push((name.to_owned(), *start))
+            } else {
+                collect_tables(from, tables)
+            }
+            for node in filters.iter().chain(groups).chain(ops) {
+                collect_tables(node, tables)
+            }
+        }
+        AstNode::SubQuery { exp, sql, .. } => {
+            collect_tables(exp, tables);
+            collect_tables(sql, tables)
+        }
+        AstNode::UnaryOp { op, exp } => {
+            collect_tables(op, tables);
+            collect_tables(exp, tables)
+        }
+        AstNode::BinOp { lhs, rhs, .. } => {
+            collect_tables(lhs, tables);
+            collect_tables(rhs, tables)
+        }
+        AstNode::Call { f, args, .. } => {
+            collect_tables(f, tables);
+            for arg in args {
+                collect_tables(arg, tables)
+            }
+        }
+        AstNode::Series { exp, .. } => collect_tables(exp, tables),
+        AstNode::With { sqls, sql, .. } => {
+            for sql in sqls {
+                collect_tables(sql, tables)
+            }
+            collect_tables(sql, tables)
+        }
+        _ => {}
+    }
+}
+
+// a subquery in scalar or 'in' context has to produce exactly one column
+pub(crate) fn check_single_column(sql: &AstNode) -> Result<(), String> {
+    if let AstNode::Sql {
+        op,
+        group_type,
+        groups,
+        ops,
+        ..
+    } = sql
+    {
+        if op != "select" {
+            return Err(format!("subquery requires 'select', got '{}'", op));
+        }
+        if ops.is_empty() {
+            return Err("subquery requires exactly one column, got all columns".to_owned());
+        }
+        // dyn and rolling take interval as their first group
+        let group_num = if group_type == "by" || groups.is_empty() {
+            groups.len()
+        } else {
+            groups.len() - 1
+        };
+        if group_num + ops.len() != 1 {
+            return Err(format!(
+                "subquery requires exactly one column, got {}",
+                group_num + ops.len()
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn parse_sql_col_exp(pair: Pair<Rule>, opts: &ParseOptions) -> Result<AstNode, PestError<Rule>> {
+    let source_id = opts.source_id;
     match pair.as_rule() {
-        Rule::SeriesExp => parse_sql_col_exp(pair.into_inner().next().unwrap(), source_id),
+        Rule::SeriesExp => parse_sql_col_exp(pair.into_inner().next().unwrap(), opts),
         Rule::RenameSeriesExp => {
             let mut pairs = pair.into_inner();
             let name = pairs.next().unwrap().as_str();
-            let exp = parse_exp(pairs.next().unwrap(), source_id)?;
+            let exp = parse_exp(pairs.next().unwrap(), opts)?;
             Ok(AstNode::Series {
                 name: name.to_owned(),
                 exp: Box::new(exp),
@@ -950,7 +1702,7 @@ fn parse_sql_col_exp(pair: Pair<Rule>, source_id: usize) -> Result<AstNode, Pest
             start: pair.as_span().start(),
             source_id,
         }),
-        _ => parse_exp(pair, source_id),
+        _ => parse_exp(pair, opts),
     }
 }
 
@@ -958,7 +1710,48 @@ fn raise_error(msg: String, span: Span) -> PestError<Rule> {
     PestError::new_from_span(ErrorVariant::CustomError { message: msg }, span)
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseOptions {
+    // of literals without a timezone, a name in the tz database
+    pub timezone: String,
+    pub source_id: usize,
+    // '$1' and '$name' placeholders of parameterized queries
+    pub params: bool,
+    // select, update, delete, insert, set operations and the like
+    pub sql: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            timezone: "UTC".to_owned(),
+            source_id: 0,
+            params: true,
+            sql: true,
+        }
+    }
+}
+
+impl ParseOptions {
+    // in the timezone of the host
+    pub fn local(source_id: usize) -> Self {
+        Self {
+            timezone: local_timezone(),
+            source_id,
+            ..Default::default()
+        }
+    }
+}
+
+// literals without a timezone are in the timezone of the host
 pub fn parse(source: &str, source_id: usize) -> Result<Vec<AstNode>, PestError<Rule>> {
+    parse_with_options(source, &ParseOptions::local(source_id))
+}
+
+pub fn parse_with_options(
+    source: &str,
+    opts: &ParseOptions,
+) -> Result<Vec<AstNode>, PestError<Rule>> {
     let mut ast = vec![];
     let pairs = JParser::parse(Rule::Program, source).map_err(|e| {
         if e.to_string().len() > 200 {
@@ -989,7 +1782,7 @@ pub fn parse(source: &str, source_id: usize) -> Result<Vec<AstNode>, PestError<R
     })?;
     for pair in pairs {
         if let Rule::Exp = pair.as_rule() {
-            ast.push(parse_exp(pair, source_id)?);
+            ast.push(parse_exp(pair, opts)?);
         }
     }
     Ok(ast)
@@ -1002,6 +1795,29 @@ pub fn parse_date(date: &str) -> Result<i32, String> {
     }
 }
 
+// months '2024-03m', quarters '2024Q1' and ISO weeks '2024W10'
+pub fn parse_calendar(s: &str) -> Result<J, String> {
+    let err = || format!("Not a valid month, quarter or week, {}", s);
+    let year = s[..4].parse::<i32>().map_err(|_| err())?;
+    let n = s[5..]
+        .trim_end_matches('m')
+        .parse::<u32>()
+        .map_err(|_| err())?;
+    match &s[4..5] {
+        "-" if (1..=12).contains(&n) => Ok(J::Month((year - 1970) * 12 + n as i32 - 1)),
+        "Q" if (1..=4).contains(&n) => Ok(J::Quarter((year - 1970) * 4 + n as i32 - 1)),
+        "W" => {
+            let monday = chrono::NaiveDate::from_isoywd_opt(year, n, chrono::Weekday::Mon)
+                .ok_or_else(err)?;
+            // 1970W01 starts from 1969.12.29
+            Ok(J::Week(
+                (monday.num_days_from_ce() - UNIX_EPOCH_DAY + 3) / 7,
+            ))
+        }
+        _ => Err(err()),
+    }
+}
+
 pub fn parse_time(time: &str) -> Result<i64, String> {
     let err = || format!("Not a valid time, {}", time);
     let mut nano = "";
@@ -1033,7 +1849,28 @@ pub fn parse_time(time: &str) -> Result<i64, String> {
     Ok((hh * 3600 + mm * 60 + ss) * 1000_000_000 + nano)
 }
 
-pub fn parse_duration(duration: &str) -> Result<i64, String> {
+// durations of fixed units are in nanoseconds, and of calendar units are periods
+// units up to hours are fixed nanoseconds, days and longer units follow the calendar
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum ParsedDuration {
+    Fixed(i64),
+    Calendar { months: i32, days: i32 },
+}
+
+impl ParsedDuration {
+    pub fn into_j(self) -> J {
+        match self {
+            ParsedDuration::Fixed(ns) => J::Duration(ns),
+            ParsedDuration::Calendar { months, days } => J::Period {
+                months,
+                days,
+                ns: 0,
+            },
+        }
+    }
+}
+
+pub fn parse_duration(duration: &str) -> Result<ParsedDuration, String> {
     let err = || format!("Not a valid duration, {}", duration);
     if duration.contains("D") {
         let v: Vec<&str> = duration.split("D").collect();
@@ -1045,63 +1882,109 @@ pub fn parse_duration(duration: &str) -> Result<i64, String> {
         } else {
             parse_time(time).map_err(|_| err())?
         };
-        Ok(if is_neg {
+        return Ok(ParsedDuration::Fixed(if is_neg {
             day * NS_IN_DAY - nano
         } else {
             day * NS_IN_DAY + nano
-        })
-    } else if duration.ends_with("ns") {
-        duration[..duration.len() - 2]
-            .parse::<i64>()
-            .map_err(|_| err())
-    } else if duration.ends_with("s") {
-        duration[..duration.len() - 1]
-            .parse::<i64>()
-            .map_err(|_| err())
-            .map(|u| u * 1000_000_000)
-    } else if duration.ends_with("m") {
-        duration[..duration.len() - 1]
-            .parse::<i64>()
-            .map_err(|_| err())
-            .map(|u| u * 60_000_000_000)
-    } else if duration.ends_with("h") {
-        duration[..duration.len() - 1]
-            .parse::<i64>()
-            .map_err(|_| err())
-            .map(|u| u * 3_600_000_000_000)
-    } else {
-        return Err(err());
+        }));
     }
-}
-
-pub fn parse_datetime(dt: &str) -> Result<i64, String> {
-    let datetime = if dt.ends_with("T") {
-        format!("{}00:00:00.0", dt)
-    } else {
-        dt.to_owned()
+    let i = duration
+        .find(|c: char| c.is_ascii_alphabetic())
+        .ok_or_else(err)?;
+    let (n, unit) = duration.split_at(i);
+    let n = n.parse::<i64>().map_err(|_| err())?;
+    let nanos = |unit: i64| {
+        n.checked_mul(unit)
+            .map(ParsedDuration::Fixed)
+            .ok_or_else(err)
     };
-    match chrono::NaiveDateTime::parse_from_str(&datetime, "%Y-%m-%dT%H:%M:%S%.f") {
-        Ok(d) => Ok(Local.from_local_datetime(&d).unwrap().timestamp_millis()),
-        Err(_) => Err(format!("Not a valid datetime, {}", dt)),
+    let months = |unit: i64| {
+        n.checked_mul(unit)
+            .and_then(|months| i32::try_from(months).ok())
+            .map(|months| ParsedDuration::Calendar { months, days: 0 })
+            .ok_or_else(err)
+    };
+    let days = |unit: i64| {
+        n.checked_mul(unit)
+            .and_then(|days| i32::try_from(days).ok())
+            .map(|days| ParsedDuration::Calendar { months: 0, days })
+            .ok_or_else(err)
+    };
+    match unit {
+        "ns" => nanos(1),
+        "us" => nanos(1_000),
+        "ms" => nanos(1_000_000),
+        "s" => nanos(1_000_000_000),
+        "m" => nanos(60_000_000_000),
+        "h" => nanos(3_600_000_000_000),
+        "d" => days(1),
+        "w" => days(7),
+        "mo" => months(1),
+        "q" => months(3),
+        "y" => months(12),
+        _ => Err(err()),
     }
 }
 
-pub fn parse_timestamp(ts: &str) -> Result<i64, String> {
-    let timestamp = if ts.ends_with("D") {
-        format!("{}00:00:00.0", ts)
+pub fn local_timezone() -> String {
+    iana_time_zone::get_timezone().unwrap_or("UTC".to_owned())
+}
+
+// '@zone' is a name in the tz database, offsets and 'Z' are stored as 'UTC', others are in 'timezone'
+fn parse_zoned(
+    s: &str,
+    sep: char,
+    name: &str,
+    timezone: &str,
+) -> Result<(chrono::DateTime<chrono::Utc>, String), String> {
+    let (local, zone) = match s.find(['@', '+', 'Z']).or_else(|| {
+        // '-' of an offset comes after the time
+        s.rfind('-').filter(|i| *i > 10)
+    }) {
+        Some(i) => (&s[..i], &s[i..]),
+        None => (s, ""),
+    };
+    let local = if local.ends_with(sep) {
+        format!("{}00:00:00.0", local)
     } else {
-        ts.to_owned()
+        local.to_owned()
     };
-    match chrono::NaiveDateTime::parse_from_str(&timestamp, "%Y-%m-%dD%H:%M:%S%.f") {
-        Ok(d) => Ok(Local
-            .from_local_datetime(&d)
-            .unwrap()
-            .timestamp_nanos_opt()
-            .unwrap_or(0)),
-        Err(_) => Err(format!("Not a valid timestamp, {}", ts)),
+    let invalid = || format!("Not a valid {}, {}", name, s);
+    let d = chrono::NaiveDateTime::parse_from_str(&local, &format!("%Y-%m-%d{}%H:%M:%S%.f", sep))
+        .map_err(|_| invalid())?;
+    match zone {
+        "Z" => Ok((d.and_utc(), "UTC".to_owned())),
+        "@" => Err(invalid()),
+        _ if zone.starts_with(['+', '-']) => {
+            let offset = zone.parse::<chrono::FixedOffset>().map_err(|_| invalid())?;
+            offset
+                .from_local_datetime(&d)
+                .single()
+                .map(|dt| (dt.to_utc(), "UTC".to_owned()))
+                .ok_or_else(invalid)
+        }
+        _ => {
+            let zone = zone.strip_prefix('@').unwrap_or(timezone);
+            let tz = zone
+                .parse::<chrono_tz::Tz>()
+                .map_err(|_| format!("Unknown timezone '{}', {}", zone, s))?;
+            tz.from_local_datetime(&d)
+                .earliest()
+                .map(|dt| (dt.to_utc(), zone.to_owned()))
+                .ok_or_else(|| format!("Not a valid {} in {}, {}", name, zone, s))
+        }
     }
 }
 
+pub fn parse_datetime(dt: &str, timezone: &str) -> Result<(i64, String), String> {
+    parse_zoned(dt, 'T', "datetime", timezone).map(|(d, tz)| (d.timestamp_millis(), tz))
+}
+
+pub fn parse_timestamp(ts: &str, timezone: &str) -> Result<(i64, String), String> {
+    parse_zoned(ts, 'D', "timestamp", timezone)
+        .map(|(d, tz)| (d.timestamp_nanos_opt().unwrap_or(0), tz))
+}
+
 pub fn is_keyword(s: &str) -> bool {
     if vec![
         "select", "update", "delete", "group", "by", "from", "where", "order", "take", "sort",
@@ -1118,7 +2001,7 @@ pub fn is_keyword(s: &str) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use crate::parser::{parse_duration, parse_time};
+    use crate::parser::{parse_duration, parse_time, ParsedDuration};
 
     #[test]
     fn test_parse_time() {
@@ -1133,10 +2016,43 @@ mod tests {
 
     #[test]
     fn test_parse_duration() {
-        assert_eq!(parse_duration("-0D23:59:59").unwrap(), -86399000000000);
-        assert_eq!(parse_duration("0D23:59:59").unwrap(), 86399000000000);
-        assert_eq!(parse_duration("1D23:59:59").unwrap(), 172799000000000);
-        assert_eq!(parse_duration("100D23:59:59").unwrap(), 8726399000000000);
-        assert!(parse_duration("100D23:60:59.123456789").is_err())
+        assert_eq!(
+            parse_duration("-0D23:59:59").unwrap(),
+            ParsedDuration::Fixed(-86399000000000)
+        );
+        assert_eq!(
+            parse_duration("0D23:59:59").unwrap(),
+            ParsedDuration::Fixed(86399000000000)
+        );
+        assert_eq!(
+            parse_duration("1D23:59:59").unwrap(),
+            ParsedDuration::Fixed(172799000000000)
+        );
+        assert_eq!(
+            parse_duration("100D23:59:59").unwrap(),
+            ParsedDuration::Fixed(8726399000000000)
+        );
+        assert!(parse_duration("100D23:60:59.123456789").is_err());
+        assert_eq!(
+            parse_duration("-5ms").unwrap(),
+            ParsedDuration::Fixed(-5_000_000)
+        );
+        assert_eq!(parse_duration("3us").unwrap(), ParsedDuration::Fixed(3_000));
+        assert_eq!(
+            parse_duration("2w").unwrap(),
+            ParsedDuration::Calendar {
+                months: 0,
+                days: 14
+            }
+        );
+        assert_eq!(
+            parse_duration("-1q").unwrap(),
+            ParsedDuration::Calendar {
+                months: -3,
+                days: 0
+            }
+        );
+        assert!(parse_duration("9999999999h").is_err());
+        assert!(parse_duration("3000000000y").is_err());
     }
 }
@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use crate::{errors::trace, AstNode};
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Source {
+    pub path: String,
+    pub text: String,
+}
+
+// sources loaded by an engine, ids are assigned in loading order and used as 'source_id' of nodes
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    sources: Vec<Source>,
+    paths: HashMap<String, usize>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // reloading a file keeps its id, sources without a path always get a new id
+    pub fn add(&mut self, path: &str, text: &str) -> usize {
+        let source = Source {
+            path: path.to_owned(),
+            text: text.to_owned(),
+        };
+        if let Some(&id) = self.paths.get(path) {
+            self.sources[id] = source;
+            return id;
+        }
+        let id = self.sources.len();
+        if !path.is_empty() {
+            self.paths.insert(path.to_owned(), id);
+        }
+        self.sources.push(source);
+        id
+    }
+
+    // replaces the text of a loaded source and keeps its path, false for unknown sources
+    pub fn set_text(&mut self, source_id: usize, text: &str) -> bool {
+        match self.sources.get_mut(source_id) {
+            Some(source) => {
+                source.text = text.to_owned();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn get(&self, source_id: usize) -> Option<&Source> {
+        self.sources.get(source_id)
+    }
+
+    pub fn id_of(&self, path: &str) -> Option<usize> {
+        self.paths.get(path).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.sources.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+
+    // 1-based line and column of a byte offset
+    pub fn line_col(&self, source_id: usize, pos: usize) -> Option<(usize, usize)> {
+        let source = self.get(source_id)?;
+        let (r, c, _) = locate(&source.text, pos.min(source.text.len()));
+        Some((r, c))
+    }
+
+    // falls back to 'msg' for unknown sources
+    pub fn trace(&self, source_id: usize, pos: usize, msg: &str) -> String {
+        match self.get(source_id) {
+            Some(source) => trace(&source.text, &source.path, pos.min(source.text.len()), msg),
+            None => msg.to_owned(),
+        }
+    }
+
+    pub fn trace_node(&self, node: &AstNode, msg: &str) -> String {
+        match node.span() {
+            Some((source_id, pos)) => self.trace(source_id, pos, msg),
+            None => msg.to_owned(),
+        }
+    }
+}
+
+// line, column and the offset of the line start, '\r\n' is a single line break
+pub(crate) fn locate(source: &str, pos: usize) -> (usize, usize, usize) {
+    let mut start = 0;
+    let mut r = 1;
+    let mut c = 1;
+    let mut chars = source.chars().peekable();
+    let mut i = 0;
+    while i < pos {
+        match chars.next() {
+            Some('\r') => {
+                if let Some(&'\n') = chars.peek() {
+                    chars.next();
+                    i += 2;
+                    r += 1;
+                    c = 1;
+                    start = i;
+                } else {
+                    i += 1;
+                    c += 1;
+                }
+            }
+            Some('\n') => {
+                i += 1;
+                r += 1;
+                c = 1;
+                start = i;
+            }
+            Some(ch) => {
+                i += ch.len_utf8();
+                c += 1;
+            }
+            None => {
+                break;
+            }
+        }
+    }
+    (r, c, start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn source_map_trace() {
+        let mut sources = SourceMap::new();
+        assert_eq!(sources.add("", "1+1"), 0);
+        let id = sources.add("a.jsm", "x = 1;\r\ny = `a + 1;");
+        assert_eq!(id, 1);
+        assert_eq!(sources.add("a.jsm", "x = 1;\r\nz = `a + 1;"), 1);
+        assert_eq!(sources.id_of("a.jsm"), Some(1));
+        assert!(sources.set_text(0, "2+2"));
+        assert!(!sources.set_text(2, "2+2"));
+        assert_eq!(sources.get(0).unwrap().text, "2+2");
+        assert_eq!(sources.len(), 2);
+
+        assert_eq!(sources.line_col(id, 8), Some((2, 1)));
+        assert_eq!(sources.line_col(id, 12), Some((2, 5)));
+        assert_eq!(sources.line_col(2, 0), None);
+
+        let nodes = parse(&sources.get(id).unwrap().text, id).unwrap();
+        let AstNode::Assign { exp, .. } = &nodes[1] else {
+            panic!("expected an assignment")
+        };
+        assert_eq!(
+            sources.trace_node(exp, "type"),
+            ["--> a.jsm2:8", "", "z = `a + 1;", "       ^", "", "= type"].join("\n")
+        );
+        assert_eq!(sources.trace(2, 0, "type"), "type");
+    }
+}
@@ -0,0 +1,406 @@
+use crate::{errors::JResult, j::J, AstNode};
+
+// walks every child node in field order, override 'visit_node' to handle nodes of interest
+// and call 'walk_node' to keep descending
+pub trait Visitor {
+    fn visit_node(&mut self, node: &AstNode) {
+        walk_node(self, node)
+    }
+
+    fn visit_j(&mut self, _j: &J) {}
+}
+
+pub trait VisitorMut {
+    fn visit_node_mut(&mut self, node: &mut AstNode) {
+        walk_node_mut(self, node)
+    }
+
+    fn visit_j_mut(&mut self, _j: &mut J) {}
+}
+
+// rebuilds nodes bottom up, e.g. binding parameters, errors stop folding
+pub trait Fold {
+    fn fold_node(&mut self, node: AstNode) -> JResult<AstNode> {
+        fold_children(self, node)
+    }
+
+    fn fold_j(&mut self, j: J) -> JResult<AstNode> {
+        Ok(AstNode::J(j))
+    }
+}
+
+pub fn walk_node<V: Visitor + ?Sized>(visitor: &mut V, node: &AstNode) {
+    match node {
+        AstNode::J(j) => visitor.visit_j(j),
+        AstNode::Fn { stmts, .. } => walk_nodes(visitor, stmts),
+        AstNode::UnaryOp { op, exp } => {
+            visitor.visit_node(op);
+            visitor.visit_node(exp);
+        }
+        AstNode::BinOp { op, lhs, rhs } => {
+            visitor.visit_node(op);
+            visitor.visit_node(lhs);
+            visitor.visit_node(rhs);
+        }
+        AstNode::Assign { exp, .. } => visitor.visit_node(exp),
+        AstNode::IndexAssign { id, indices, exp } => {
+            visitor.visit_node(id);
+            walk_nodes(visitor, indices);
+            visitor.visit_node(exp);
+        }
+        AstNode::Call { f, args, .. } => {
+            visitor.visit_node(f);
+            walk_nodes(visitor, args);
+        }
+        AstNode::If { cond, stmts } | AstNode::While { cond, stmts } => {
+            visitor.visit_node(cond);
+            walk_nodes(visitor, stmts);
+        }
+        AstNode::Try { tries, catches, .. } => {
+            walk_nodes(visitor, tries);
+            walk_nodes(visitor, catches);
+        }
+        AstNode::Return(exp) | AstNode::Raise { exp, .. } | AstNode::Series { exp, .. } => {
+            visitor.visit_node(exp)
+        }
+        AstNode::Dataframe { exps: nodes, .. }
+        | AstNode::Matrix(nodes)
+        | AstNode::Dict { values: nodes, .. }
+        | AstNode::List(nodes) => walk_nodes(visitor, nodes),
+        AstNode::Sql {
+            from,
+            filters,
+            groups,
+            ops,
+            sorts,
+            take,
+            take_by,
+            skip,
+            ..
+        } => {
+            visitor.visit_node(from);
+            walk_nodes(visitor, filters);
+            walk_nodes(visitor, groups);
+            walk_nodes(visitor, ops);
+            walk_nodes(visitor, sorts);
+            visitor.visit_node(take);
+            walk_nodes(visitor, take_by);
+            visitor.visit_node(skip);
+        }
+        AstNode::SubQuery { exp, sql, .. } => {
+            visitor.visit_node(exp);
+            visitor.visit_node(sql);
+        }
+        AstNode::With { sqls, sql, .. } => {
+            walk_nodes(visitor, sqls);
+            visitor.visit_node(sql);
+        }
+        AstNode::Explain { sql, .. } | AstNode::Pivot { sql, .. } => visitor.visit_node(sql),
+        AstNode::Insert { exp, .. } => visitor.visit_node(exp),
+        AstNode::SetOp { lhs, rhs, .. } => {
+            visitor.visit_node(lhs);
+            visitor.visit_node(rhs);
+        }
+        AstNode::Op { .. } | AstNode::Id { .. } | AstNode::Param { .. } | AstNode::Skip => (),
+    }
+}
+
+fn walk_nodes<V: Visitor + ?Sized>(visitor: &mut V, nodes: &[AstNode]) {
+    nodes.iter().for_each(|node| visitor.visit_node(node))
+}
+
+pub fn walk_node_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut AstNode) {
+    match node {
+        AstNode::J(j) => visitor.visit_j_mut(j),
+        AstNode::Fn { stmts, .. } => walk_nodes_mut(visitor, stmts),
+        AstNode::UnaryOp { op, exp } => {
+            visitor.visit_node_mut(op);
+            visitor.visit_node_mut(exp);
+        }
+        AstNode::BinOp { op, lhs, rhs } => {
+            visitor.visit_node_mut(op);
+            visitor.visit_node_mut(lhs);
+            visitor.visit_node_mut(rhs);
+        }
+        AstNode::Assign { exp, .. } => visitor.visit_node_mut(exp),
+        AstNode::IndexAssign { id, indices, exp } => {
+            visitor.visit_node_mut(id);
+            walk_nodes_mut(visitor, indices);
+            visitor.visit_node_mut(exp);
+        }
+        AstNode::Call { f, args, .. } => {
+            visitor.visit_node_mut(f);
+            walk_nodes_mut(visitor, args);
+        }
+        AstNode::If { cond, stmts } | AstNode::While { cond, stmts } => {
+            visitor.visit_node_mut(cond);
+            walk_nodes_mut(visitor, stmts);
+        }
+        AstNode::Try { tries, catches, .. } => {
+            walk_nodes_mut(visitor, tries);
+            walk_nodes_mut(visitor, catches);
+        }
+        AstNode::Return(exp) | AstNode::Raise { exp, .. } | AstNode::Series { exp, .. } => {
+            visitor.visit_node_mut(exp)
+        }
+        AstNode::Dataframe { exps: nodes, .. }
+        | AstNode::Matrix(nodes)
+        | AstNode::Dict { values: nodes, .. }
+        | AstNode::List(nodes) => walk_nodes_mut(visitor, nodes),
+        AstNode::Sql {
+            from,
+            filters,
+            groups,
+            ops,
+            sorts,
+            take,
+            take_by,
+            skip,
+            ..
+        } => {
+            visitor.visit_node_mut(from);
+            walk_nodes_mut(visitor, filters);
+            walk_nodes_mut(visitor, groups);
+            walk_nodes_mut(visitor, ops);
+            walk_nodes_mut(visitor, sorts);
+            visitor.visit_node_mut(take);
+            walk_nodes_mut(visitor, take_by);
+            visitor.visit_node_mut(skip);
+        }
+        AstNode::SubQuery { exp, sql, .. } => {
+            visitor.visit_node_mut(exp);
+            visitor.visit_node_mut(sql);
+        }
+        AstNode::With { sqls, sql, .. } => {
+            walk_nodes_mut(visitor, sqls);
+            visitor.visit_node_mut(sql);
+        }
+        AstNode::Explain { sql, .. } | AstNode::Pivot { sql, .. } => visitor.visit_node_mut(sql),
+        AstNode::Insert { exp, .. } => visitor.visit_node_mut(exp),
+        AstNode::SetOp { lhs, rhs, .. } => {
+            visitor.visit_node_mut(lhs);
+            visitor.visit_node_mut(rhs);
+        }
+        AstNode::Op { .. } | AstNode::Id { .. } | AstNode::Param { .. } | AstNode::Skip => (),
+    }
+}
+
+fn walk_nodes_mut<V: VisitorMut + ?Sized>(visitor: &mut V, nodes: &mut [AstNode]) {
+    nodes
+        .iter_mut()
+        .for_each(|node| visitor.visit_node_mut(node))
+}
+
+pub fn fold_children<F: Fold + ?Sized>(folder: &mut F, node: AstNode) -> JResult<AstNode> {
+    let node = match node {
+        AstNode::J(j) => return folder.fold_j(j),
+        AstNode::Fn {
+            stmts,
+            arg_names,
+            fn_body,
+            start,
+            source_id,
+        } => AstNode::Fn {
+            stmts: fold_nodes(folder, stmts)?,
+            arg_names,
+            fn_body,
+            start,
+            source_id,
+        },
+        AstNode::UnaryOp { op, exp } => AstNode::UnaryOp {
+            op: fold_box(folder, op)?,
+            exp: fold_box(folder, exp)?,
+        },
+        AstNode::BinOp { op, lhs, rhs } => AstNode::BinOp {
+            op: fold_box(folder, op)?,
+            lhs: fold_box(folder, lhs)?,
+            rhs: fold_box(folder, rhs)?,
+        },
+        AstNode::Assign { id, exp } => AstNode::Assign {
+            id,
+            exp: fold_box(folder, exp)?,
+        },
+        AstNode::IndexAssign { id, indices, exp } => AstNode::IndexAssign {
+            id: fold_box(folder, id)?,
+            indices: fold_nodes(folder, indices)?,
+            exp: fold_box(folder, exp)?,
+        },
+        AstNode::Call {
+            f,
+            args,
+            start,
+            source_id,
+        } => AstNode::Call {
+            f: fold_box(folder, f)?,
+            args: fold_nodes(folder, args)?,
+            start,
+            source_id,
+        },
+        AstNode::If { cond, stmts } => AstNode::If {
+            cond: fold_box(folder, cond)?,
+            stmts: fold_nodes(folder, stmts)?,
+        },
+        AstNode::While { cond, stmts } => AstNode::While {
+            cond: fold_box(folder, cond)?,
+            stmts: fold_nodes(folder, stmts)?,
+        },
+        AstNode::Try {
+            tries,
+            err,
+            catches,
+        } => AstNode::Try {
+            tries: fold_nodes(folder, tries)?,
+            err,
+            catches: fold_nodes(folder, catches)?,
+        },
+        AstNode::Return(exp) => AstNode::Return(fold_box(folder, exp)?),
+        AstNode::Raise {
+            exp,
+            start,
+            source_id,
+        } => AstNode::Raise {
+            exp: fold_box(folder, exp)?,
+            start,
+            source_id,
+        },
+        AstNode::Dataframe {
+            exps,
+            start,
+            source_id,
+        } => AstNode::Dataframe {
+            exps: fold_nodes(folder, exps)?,
+            start,
+            source_id,
+        },
+        AstNode::Matrix(exps) => AstNode::Matrix(fold_nodes(folder, exps)?),
+        AstNode::Dict { keys, values } => AstNode::Dict {
+            keys,
+            values: fold_nodes(folder, values)?,
+        },
+        AstNode::List(exps) => AstNode::List(fold_nodes(folder, exps)?),
+        AstNode::Series { name, exp } => AstNode::Series {
+            name,
+            exp: fold_box(folder, exp)?,
+        },
+        AstNode::Sql {
+            op,
+            from,
+            filters,
+            group_type,
+            groups,
+            ops,
+            sorts,
+            take,
+            take_by,
+            skip,
+            distinct,
+            source_id,
+            start,
+        } => AstNode::Sql {
+            op,
+            from: fold_box(folder, from)?,
+            filters: fold_nodes(folder, filters)?,
+            group_type,
+            groups: fold_nodes(folder, groups)?,
+            ops: fold_nodes(folder, ops)?,
+            sorts: fold_nodes(folder, sorts)?,
+            take: fold_box(folder, take)?,
+            take_by: fold_nodes(folder, take_by)?,
+            skip: fold_box(folder, skip)?,
+            distinct,
+            source_id,
+            start,
+        },
+        AstNode::SubQuery {
+            kind,
+            exp,
+            sql,
+            start,
+            source_id,
+        } => AstNode::SubQuery {
+            kind,
+            exp: fold_box(folder, exp)?,
+            sql: fold_box(folder, sql)?,
+            start,
+            source_id,
+        },
+        AstNode::With {
+            names,
+            sqls,
+            sql,
+            start,
+            source_id,
+        } => AstNode::With {
+            names,
+            sqls: fold_nodes(folder, sqls)?,
+            sql: fold_box(folder, sql)?,
+            start,
+            source_id,
+        },
+        AstNode::Explain {
+            sql,
+            start,
+            source_id,
+        } => AstNode::Explain {
+            sql: fold_box(folder, sql)?,
+            start,
+            source_id,
+        },
+        AstNode::Pivot {
+            kind,
+            sql,
+            index,
+            name,
+            start,
+            source_id,
+        } => AstNode::Pivot {
+            kind,
+            sql: fold_box(folder, sql)?,
+            index,
+            name,
+            start,
+            source_id,
+        },
+        AstNode::Insert {
+            kind,
+            table,
+            keys,
+            exp,
+            start,
+            source_id,
+        } => AstNode::Insert {
+            kind,
+            table,
+            keys,
+            exp: fold_box(folder, exp)?,
+            start,
+            source_id,
+        },
+        AstNode::SetOp {
+            op,
+            lhs,
+            rhs,
+            start,
+            source_id,
+        } => AstNode::SetOp {
+            op,
+            lhs: fold_box(folder, lhs)?,
+            rhs: fold_box(folder, rhs)?,
+            start,
+            source_id,
+        },
+        AstNode::Op { .. } | AstNode::Id { .. } | AstNode::Param { .. } | AstNode::Skip => node,
+    };
+    Ok(node)
+}
+
+pub fn fold_nodes<F: Fold + ?Sized>(folder: &mut F, nodes: Vec<AstNode>) -> JResult<Vec<AstNode>> {
+    nodes
+        .into_iter()
+        .map(|node| folder.fold_node(node))
+        .collect()
+}
+
+fn fold_box<F: Fold + ?Sized>(folder: &mut F, node: Box<AstNode>) -> JResult<Box<AstNode>> {
+    Ok(Box::new(folder.fold_node(*node)?))
+}
@@ -0,0 +1,220 @@
+use jasmine::{j::J, parse_ansi_sql, parse_ansi_sql_with_options, AstNode, ParseOptions};
+
+fn name(node: &AstNode) -> String {
+    match node {
+        AstNode::Id { name, .. } | AstNode::Op { name, .. } | AstNode::Series { name, .. } => {
+            name.to_owned()
+        }
+        AstNode::Call { f, .. } => name(f),
+        AstNode::BinOp { op, .. } => name(op),
+        AstNode::UnaryOp { op, .. } => name(op),
+        _ => format!("{:?}", node),
+    }
+}
+
+#[test]
+fn translate_ansi_sql() {
+    let sql = parse_ansi_sql(
+        "SELECT sym, SUM(qty) AS total, AVG(price), COUNT(*)
+        FROM t
+        WHERE qty > 10 AND sym IN ('a', 'b') AND price BETWEEN 1 AND 2.5
+        GROUP BY sym
+        ORDER BY sym DESC
+        LIMIT 10;",
+        1,
+    )
+    .unwrap();
+    if let AstNode::Sql {
+        op,
+        from,
+        filters,
+        group_type,
+        groups,
+        ops,
+        sorts,
+        take,
+        source_id,
+        ..
+    } = sql
+    {
+        assert_eq!(op, "select");
+        assert_eq!(name(&from), "t");
+        assert_eq!(
+            filters.iter().map(name).collect::<Vec<_>>(),
+            vec![">", "in", "between"]
+        );
+        assert_eq!(group_type, "by");
+        assert_eq!(groups.iter().map(name).collect::<Vec<_>>(), vec!["sym"]);
+        assert_eq!(
+            ops.iter().map(name).collect::<Vec<_>>(),
+            vec!["total", "mean", "count"]
+        );
+        assert_eq!(sorts.iter().map(name).collect::<Vec<_>>(), vec!["-sym"]);
+        assert_eq!(*take, AstNode::J(J::I64(10)));
+        assert_eq!(source_id, 1);
+    } else {
+        panic!("expected sql, got {:?}", sql)
+    }
+}
+
+#[test]
+fn translate_ansi_sql_expressions() {
+    let sql = parse_ansi_sql(
+        "select * from (select sym, qty from t) as s where s.sym like 'a%' or not qty = -1",
+        0,
+    )
+    .unwrap();
+    if let AstNode::Sql {
+        from, filters, ops, ..
+    } = sql
+    {
+        assert!(matches!(*from, AstNode::Sql { .. }));
+        assert!(ops.is_empty());
+        assert_eq!(filters.len(), 1);
+        if let AstNode::BinOp { op, lhs, rhs } = &filters[0] {
+            assert_eq!(name(op), "|");
+            if let AstNode::BinOp { rhs, .. } = lhs.as_ref() {
+                assert_eq!(**rhs, AstNode::J(J::String("^a.*$".to_owned())));
+            }
+            assert_eq!(name(rhs), "not");
+        } else {
+            panic!("expected bin op, got {:?}", filters[0])
+        }
+    }
+
+    let sql = parse_ansi_sql(
+        "select sym from t where sym not in (select sym from blacklist)",
+        0,
+    )
+    .unwrap();
+    if let AstNode::Sql { filters, .. } = sql {
+        assert!(matches!(&filters[0], AstNode::SubQuery { kind, .. } if kind == "not in"));
+    }
+
+    let sql = parse_ansi_sql("select distinct sym from t limit 10 offset 5", 0).unwrap();
+    if let AstNode::Sql {
+        take,
+        skip,
+        distinct,
+        ..
+    } = sql
+    {
+        assert!(distinct);
+        assert_eq!(*take, AstNode::J(J::I64(10)));
+        assert_eq!(*skip, AstNode::J(J::I64(5)));
+    }
+}
+
+#[test]
+fn translate_ansi_set_op() {
+    let sql = parse_ansi_sql(
+        "select sym from t union all select sym from u intersect select sym from v except select sym from w",
+        0,
+    )
+    .unwrap();
+    if let AstNode::SetOp { op, lhs, rhs, .. } = sql {
+        assert_eq!(op, "except");
+        assert!(matches!(*rhs, AstNode::Sql { .. }));
+        if let AstNode::SetOp { op, lhs, rhs, .. } = *lhs {
+            assert_eq!(op, "union all");
+            assert!(matches!(*lhs, AstNode::Sql { .. }));
+            assert!(matches!(*rhs, AstNode::SetOp { op, .. } if op == "intersect"));
+        } else {
+            panic!("expected set op, got {:?}", lhs)
+        }
+    } else {
+        panic!("expected set op, got {:?}", sql)
+    }
+}
+
+#[test]
+fn translate_ansi_sql_errors() {
+    let cases = [
+        (
+            "select * from t join u on t.id = u.id",
+            "'JOIN' is not supported",
+        ),
+        (
+            "select sym, count(*) from t group by sym having count(*) > 1",
+            "'HAVING' is not supported",
+        ),
+        (
+            "select * from t, u",
+            "multiple tables in 'FROM' are not supported",
+        ),
+        (
+            "select sym, qty from t group by sym",
+            "column 'qty' must appear in 'GROUP BY'",
+        ),
+        ("select foo(qty) from t", "unsupported function 'foo'"),
+        (
+            "select sum(qty) over (partition by sym) from t",
+            "window function is not supported",
+        ),
+        ("select * from t where u.sym = 'a'", "unknown table 'u'"),
+        (
+            "select * from t order by qty + 1",
+            "'ORDER BY' requires a column name",
+        ),
+        (
+            "select * from t order by 2",
+            "position 2 is out of range 1..0",
+        ),
+        (
+            "select case when qty > 1 then 1 else 0 end from t",
+            "'CASE' is not supported",
+        ),
+        ("select 1", "'FROM' is required"),
+        (
+            "select * from t limit 99999999999999999999",
+            "number too large to fit in target type",
+        ),
+    ];
+    for (sql, msg) in cases {
+        let err = parse_ansi_sql(sql, 0).unwrap_err().to_string();
+        assert!(err.contains(msg), "{}: {}", sql, err);
+    }
+}
+
+#[test]
+fn translate_ansi_sql_timezone() {
+    let opts = ParseOptions {
+        timezone: "Asia/Tokyo".to_owned(),
+        ..Default::default()
+    };
+    let sql = parse_ansi_sql_with_options(
+        "SELECT * FROM t WHERE time > TIMESTAMP '2024-01-01 09:00:00'",
+        &opts,
+    )
+    .unwrap();
+    if let AstNode::Sql { filters, .. } = &sql {
+        assert!(
+            matches!(&filters[0], AstNode::BinOp { rhs, .. } if **rhs == AstNode::J(J::Timestamp {
+                ns: 1_704_067_200_000_000_000,
+                timezone: "Asia/Tokyo".to_owned(),
+            })),
+            "{:?}",
+            filters
+        );
+    } else {
+        panic!("expected sql, got {:?}", sql)
+    }
+}
+
+#[test]
+fn translate_ansi_sql_group_by_expression() {
+    let sql = parse_ansi_sql(
+        "SELECT UPPER(sym), SUM(qty) FROM t GROUP BY UPPER( sym )",
+        0,
+    )
+    .unwrap();
+    if let AstNode::Sql { groups, ops, .. } = sql {
+        assert_eq!(
+            groups.iter().map(name).collect::<Vec<_>>(),
+            vec!["uppercase"]
+        );
+        assert_eq!(ops.iter().map(name).collect::<Vec<_>>(), vec!["sum"]);
+    } else {
+        panic!("expected sql, got {:?}", sql)
+    }
+}
@@ -0,0 +1,35 @@
+use std::collections::HashMap;
+
+use jasmine::{bind, j::J, parse, AstNode};
+
+#[test]
+fn bind_params() {
+    let nodes = parse("select from t where qty > $1, sym == $sym; x = [$1, 2]", 0).unwrap();
+    let params = HashMap::from([
+        ("1".to_owned(), J::I64(10)),
+        ("sym".to_owned(), J::Cat("a".to_owned())),
+    ]);
+    let nodes = bind(nodes, &params).unwrap();
+    if let AstNode::Sql { filters, .. } = &nodes[0] {
+        assert!(matches!(
+            &filters[0],
+            AstNode::BinOp { rhs, .. } if rhs.as_ref() == &AstNode::J(J::I64(10))
+        ));
+        assert!(matches!(
+            &filters[1],
+            AstNode::BinOp { rhs, .. } if rhs.as_ref() == &AstNode::J(J::Cat("a".to_owned()))
+        ));
+    } else {
+        panic!("expected sql, got {:?}", nodes[0])
+    }
+    assert!(matches!(
+        &nodes[1],
+        AstNode::Assign { exp, .. } if matches!(exp.as_ref(), AstNode::List(exps) if exps[0] == AstNode::J(J::I64(10)))
+    ));
+
+    let nodes = parse("select from t take $n", 0).unwrap();
+    assert_eq!(
+        bind(nodes, &params).unwrap_err().to_string(),
+        "parameter '$n' is not bound"
+    );
+}
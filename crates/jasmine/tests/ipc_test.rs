@@ -0,0 +1,221 @@
+use indexmap::IndexMap;
+use jasmine::{
+    ipc::{self, deserialize, serialize},
+    j::J,
+    parse,
+};
+use ndarray::{array, ArcArray2};
+use polars::{df, prelude::NamedFrom, series::Series};
+
+// bytes written by 'serialize' of jasminum/serde.py
+fn assert_golden(j: J, expected: &[u8]) {
+    assert_eq!(serialize(&j, false).unwrap(), expected, "{:?}", j);
+    assert_eq!(deserialize(expected).unwrap(), j);
+}
+
+#[test]
+fn ipc_scalars() {
+    assert_golden(J::Null, &[0; 8]);
+    assert_golden(J::Boolean(true), &[1, 0, 0, 0, 1, 0, 0, 0]);
+    assert_golden(
+        J::I64(-2),
+        &[
+            2, 0, 0, 0, 0, 0, 0, 0, 254, 255, 255, 255, 255, 255, 255, 255,
+        ],
+    );
+    assert_golden(
+        J::F64(1.5),
+        &[8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xF8, 0x3F],
+    );
+    assert_golden(J::Date(19723), &[3, 0, 0, 0, 0x0B, 0x4D, 0, 0]);
+    assert_golden(
+        J::Time(1_000),
+        &[4, 0, 0, 0, 0, 0, 0, 0, 0xE8, 0x03, 0, 0, 0, 0, 0, 0],
+    );
+    assert_golden(
+        J::Duration(7),
+        &[7, 0, 0, 0, 0, 0, 0, 0, 7, 0, 0, 0, 0, 0, 0, 0],
+    );
+    assert_golden(
+        J::Datetime {
+            ms: 1,
+            timezone: "UTC".to_owned(),
+        },
+        &[
+            5, 0, 0, 0, 11, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, b'U', b'T', b'C', 0, 0, 0, 0, 0,
+        ],
+    );
+    assert_golden(
+        J::Timestamp {
+            ns: 2,
+            timezone: "".to_owned(),
+        },
+        &[6, 0, 0, 0, 8, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0],
+    );
+    assert_golden(
+        J::String("héllo".to_owned()),
+        &[
+            9, 0, 0, 0, 6, 0, 0, 0, b'h', 0xC3, 0xA9, b'l', b'l', b'o', 0, 0,
+        ],
+    );
+    assert_golden(
+        J::Cat("abcdefgh".to_owned()),
+        &[
+            10, 0, 0, 0, 8, 0, 0, 0, b'a', b'b', b'c', b'd', b'e', b'f', b'g', b'h',
+        ],
+    );
+    assert_golden(
+        J::Err("oops".to_owned()),
+        &[16, 0, 0, 0, 4, 0, 0, 0, b'o', b'o', b'p', b's', 0, 0, 0, 0],
+    );
+}
+
+#[test]
+fn ipc_collections() {
+    assert_golden(
+        J::MixedList(vec![J::Boolean(true), J::Null]),
+        &[
+            13, 0, 0, 0, 24, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0,
+        ],
+    );
+    assert_golden(
+        J::Dict(IndexMap::from([
+            ("a".to_owned(), J::I64(1)),
+            ("bc".to_owned(), J::String("x".to_owned())),
+        ])),
+        &[
+            14, 0, 0, 0, 64, 0, 0, 0, 2, 0, 0, 0, 11, 0, 0, 0, 1, 0, 0, 0, 3, 0, 0, 0, b'a', b'b',
+            b'c', 0, 0, 0, 0, 0, 32, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0,
+            0, 0, 9, 0, 0, 0, 1, 0, 0, 0, b'x', 0, 0, 0, 0, 0, 0, 0,
+        ],
+    );
+    let nested = J::MixedList(vec![
+        J::Dict(IndexMap::from([
+            ("key".to_owned(), J::MixedList(vec![J::Date(-1)])),
+            ("".to_owned(), J::Dict(IndexMap::new())),
+        ])),
+        J::Cat("a".to_owned()),
+    ]);
+    let bytes = serialize(&nested, false).unwrap();
+    assert_eq!(bytes.len() % 8, 0);
+    assert_eq!(deserialize(&bytes).unwrap(), nested);
+}
+
+#[test]
+fn ipc_series_and_dataframes() {
+    let s = Series::new("qty".into(), [Some(1i64), None, Some(3)]);
+    let bytes = serialize(&J::Series(s.clone()), true).unwrap();
+    assert_eq!(bytes[..4], [11, 0, 0, 0]);
+    assert_eq!(&bytes[8..14], b"ARROW1");
+    assert_eq!(bytes.len() % 8, 0);
+    assert_eq!(deserialize(&bytes).unwrap(), J::Series(s));
+
+    let df = df!("sym" => ["a", "b"], "price" => [1.5, 2.5]).unwrap();
+    let j = J::MixedList(vec![J::DataFrame(df), J::I64(1)]);
+    assert_eq!(deserialize(&serialize(&j, false).unwrap()).unwrap(), j);
+}
+
+#[test]
+fn ipc_calendar_types() {
+    let j = parse("[2024-02m, 2024Q2, 2024W03]", 0)
+        .unwrap()
+        .pop()
+        .unwrap()
+        .as_j()
+        .unwrap();
+    let dates = J::MixedList(vec![J::Date(19754), J::Date(19814), J::Date(19737)]);
+    assert_eq!(
+        serialize(&j, false).unwrap(),
+        serialize(&dates, false).unwrap()
+    );
+}
+
+#[test]
+fn ipc_matrices() {
+    let m = array![[1.0, 2.0], [3.0, 4.0]].into_shared();
+    let mut expected = vec![12, 0, 0, 0, 48, 0, 0, 0, 2, 0, 0, 0, 2, 0, 0, 0];
+    expected.extend([0; 8]);
+    [1.0f64, 2.0, 3.0, 4.0]
+        .iter()
+        .for_each(|v| expected.extend(v.to_le_bytes()));
+    assert_golden(J::Matrix(m.clone()), &expected);
+
+    // transposed matrices are column major
+    let t = m.t().to_shared();
+    let bytes = serialize(&J::Matrix(t.clone()), false).unwrap();
+    assert_eq!(bytes[16], 1);
+    assert_eq!(bytes[24..], expected[24..]);
+    assert_eq!(deserialize(&bytes).unwrap(), J::Matrix(t.clone()));
+
+    let j = J::MixedList(vec![
+        J::Matrix(ArcArray2::zeros((0, 3))),
+        J::Matrix(array![[1.5, f64::INFINITY, -0.0]].into_shared()),
+    ]);
+    assert_eq!(deserialize(&serialize(&j, false).unwrap()).unwrap(), j);
+}
+
+#[test]
+fn ipc_invalid_matrices() {
+    let t = array![[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]].t().to_shared();
+    let bytes = serialize(&J::Matrix(t.clone()), false).unwrap();
+    let mut invalid = bytes.clone();
+    invalid[16] = 2;
+    assert!(deserialize(&invalid).is_err());
+    invalid[16] = 0;
+    invalid[8] = 4;
+    assert!(deserialize(&invalid).is_err());
+}
+
+#[test]
+fn ipc_header() {
+    let header = ipc::header(ipc::SYNC, 16).unwrap();
+    assert_eq!(header, [1, 1, 0, 0, 16, 0, 0, 0]);
+    assert_eq!(ipc::parse_header(&header).unwrap(), (ipc::SYNC, 16));
+    assert!(ipc::parse_header(&[0, 1, 0, 0, 16, 0, 0, 0]).is_err());
+    assert!(ipc::parse_header(&[1, 3, 0, 0, 16, 0, 0, 0]).is_err());
+    assert!(ipc::parse_header(&[1, 1]).is_err());
+    assert!(ipc::header(ipc::ASYNC, u32::MAX as usize).is_ok());
+    assert!(ipc::header(ipc::ASYNC, u32::MAX as usize + 1).is_err());
+}
+
+#[test]
+fn ipc_invalid_bytes() {
+    assert_eq!(
+        deserialize(&[17, 0, 0, 0, 0, 0, 0, 0])
+            .unwrap_err()
+            .to_string(),
+        "unsupported j type for deserialization: 17"
+    );
+    assert!(deserialize(&[]).is_err());
+    assert!(deserialize(&[2, 0, 0, 0, 0, 0, 0, 0, 1]).is_err());
+    assert!(deserialize(&[9, 0, 0, 0, 2, 0, 0, 0, 0xFF, 0xFE, 0, 0, 0, 0, 0, 0]).is_err());
+    assert!(deserialize(&[13, 0, 0, 0, 8, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0]).is_err());
+}
+
+#[test]
+fn ipc_nesting_depth() {
+    let nest = |depth: usize| {
+        (0..depth).fold(J::Null, |j, i| {
+            if i % 2 == 0 {
+                J::MixedList(vec![j])
+            } else {
+                J::Dict(IndexMap::from([("k".to_owned(), j)]))
+            }
+        })
+    };
+    let j = nest(128);
+    assert_eq!(deserialize(&serialize(&j, false).unwrap()).unwrap(), j);
+    assert_eq!(
+        deserialize(&serialize(&nest(129), false).unwrap())
+            .unwrap_err()
+            .to_string(),
+        "exceeds the max nesting depth 128 of lists and dicts"
+    );
+    // a list of itself is rejected without reading past the message
+    let mut bytes = Vec::new();
+    for _ in 0..100_000 {
+        bytes.extend([13, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0]);
+    }
+    assert!(deserialize(&bytes).is_err());
+}
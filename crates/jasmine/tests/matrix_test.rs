@@ -0,0 +1,165 @@
+use jasmine::{errors::JError, j::J, matrix, parse};
+use ndarray::{array, ArcArray2};
+use polars::{df, prelude::NamedFrom, series::Series};
+
+fn m(code: &str) -> ArcArray2<f64> {
+    match parse(code, 0).unwrap().pop().unwrap().as_j().unwrap() {
+        J::Matrix(m) => m,
+        j => panic!("expected a matrix, got {:?}", j),
+    }
+}
+
+fn assert_close(actual: &ArcArray2<f64>, expected: &ArcArray2<f64>) {
+    assert_eq!(actual.shape(), expected.shape());
+    actual
+        .iter()
+        .zip(expected.iter())
+        .for_each(|(a, e)| assert!((a - e).abs() < 1e-9, "{} vs {}", actual, expected));
+}
+
+#[test]
+fn matrix_linear_algebra() {
+    let a = m("x[[1 2] [3 4]]");
+    let b = m("x[[5 6 7] [8 9 10]]");
+    assert_eq!(
+        matrix::matmul(&a, &b).unwrap(),
+        array![[21.0, 24.0, 27.0], [47.0, 54.0, 61.0]]
+    );
+    assert!(matches!(
+        matrix::matmul(&b, &a),
+        Err(JError::MismatchedShapeErr((2, 3), (2, 2)))
+    ));
+    assert_eq!(
+        matrix::transpose(&b),
+        array![[5.0, 8.0], [6.0, 9.0], [7.0, 10.0]]
+    );
+
+    assert!((matrix::det(&a).unwrap() + 2.0).abs() < 1e-12);
+    assert_close(
+        &matrix::inverse(&a).unwrap(),
+        &array![[-2.0, 1.0], [1.5, -0.5]].into_shared(),
+    );
+    let x = matrix::solve(&a, &m("x[[5 11]]").t().to_owned().into_shared()).unwrap();
+    assert_close(&x, &array![[1.0], [2.0]].into_shared());
+
+    let singular = m("x[[1 2] [2 4]]");
+    assert_eq!(matrix::det(&singular).unwrap(), 0.0);
+    let err = matrix::inverse(&singular).unwrap_err();
+    assert_eq!(err.to_string(), "singular matrix");
+    assert!(matches!(
+        matrix::solve(&a, &b.t().to_owned().into_shared()),
+        Err(JError::MismatchedShapeErr((2, 2), (3, 2)))
+    ));
+    assert_eq!(
+        matrix::det(&b).unwrap_err().to_string(),
+        "shape error '2x3' vs '2x2'"
+    );
+}
+
+#[test]
+fn matrix_broadcast() {
+    let a = J::Matrix(m("x[[1 2] [3 4]]"));
+    let series = J::Series(Series::new("".into(), [10i64, 20]));
+    assert_eq!(
+        matrix::broadcast("+", &a, &J::I64(1)).unwrap(),
+        J::Matrix(array![[2.0, 3.0], [4.0, 5.0]].into_shared())
+    );
+    assert_eq!(
+        matrix::broadcast("-", &J::F64(1.0), &a).unwrap(),
+        J::Matrix(array![[0.0, -1.0], [-2.0, -3.0]].into_shared())
+    );
+    assert_eq!(
+        matrix::broadcast("*", &a, &series).unwrap(),
+        J::Matrix(array![[10.0, 40.0], [30.0, 80.0]].into_shared())
+    );
+    assert_eq!(
+        matrix::broadcast("**", &a, &a).unwrap(),
+        J::Matrix(array![[1.0, 4.0], [27.0, 256.0]].into_shared())
+    );
+    assert!(matches!(
+        matrix::broadcast("+", &a, &J::Series(Series::new("".into(), [1i64, 2, 3]))),
+        Err(JError::MismatchedLengthErr(2, 3))
+    ));
+    assert!(matrix::broadcast("%", &a, &a).is_err());
+    assert!(matrix::broadcast("+", &a, &J::String("a".to_owned())).is_err());
+}
+
+#[test]
+fn matrix_conversion() {
+    let df = df!["a" => [1i64, 2], "b" => [Some(0.5), None]].unwrap();
+    let a = matrix::from_dataframe(&df).unwrap();
+    assert_eq!(a.shape(), [2, 2]);
+    assert_eq!(a[[1, 0]], 2.0);
+    assert!(a[[1, 1]].is_nan());
+
+    assert!(m("x[[1 2] [2 3] [4 0n]]")[[2, 1]].is_nan());
+
+    let a = m("x[[1 2] [3 4]]");
+    assert_eq!(
+        matrix::to_dataframe(&a).unwrap(),
+        df!["col00" => [1.0, 3.0], "col01" => [2.0, 4.0]].unwrap()
+    );
+    let s = Series::new("".into(), [1i64, 2, 3]);
+    let v = matrix::from_series(&s).unwrap();
+    assert_eq!(v.shape(), [3, 1]);
+    assert_eq!(
+        matrix::to_series(&v).unwrap(),
+        Series::new("".into(), [1.0, 2.0, 3.0])
+    );
+    assert!(matrix::to_series(&a).is_err());
+    assert!(matrix::from_dataframe(&df!["s" => ["a"]].unwrap()).is_err());
+}
+
+#[test]
+fn matrix_lsq() {
+    // y = 1 + 2x
+    let x = m("x[[1 0] [1 1] [1 2] [1 3]]");
+    let out = matrix::lsq(&[1.0, 3.0, 5.0, 7.0], &x, 0.0, false).unwrap();
+    assert_close(
+        &ArcArray2::from_shape_vec((2, 1), out.coef.clone()).unwrap(),
+        &array![[1.0], [2.0]].into_shared(),
+    );
+    assert!((out.r2 - 1.0).abs() < 1e-12);
+    assert!(out.residuals.iter().all(|r| r.unwrap().abs() < 1e-9));
+
+    let y = [1.0, f64::NAN, 5.0, 7.0];
+    let out = matrix::lsq(&y, &x, 0.0, true).unwrap();
+    assert_eq!(out.residuals[1], None);
+    assert!((out.coef[1] - 2.0).abs() < 1e-9);
+    assert_eq!(
+        matrix::lsq(&y, &x, 0.0, false).err().unwrap().to_string(),
+        "'lsq' requires values without nulls, got null at row 1"
+    );
+
+    // ridge shrinks coefficients
+    let ridge = matrix::lsq(&[1.0, 3.0, 5.0, 7.0], &x, 1.0, false).unwrap();
+    assert!(ridge.coef[1] < 2.0 && ridge.r2 < 1.0);
+    assert!(matrix::lsq(&[1.0, 3.0, 5.0, 7.0], &x, -1.0, false).is_err());
+
+    assert!(matches!(
+        matrix::lsq(&[1.0, 3.0], &x, 0.0, false),
+        Err(JError::MismatchedLengthErr(2, 4))
+    ));
+    // a repeated column
+    assert!(matches!(
+        matrix::lsq(
+            &[1.0, 3.0, 5.0, 7.0],
+            &m("x[[1 1] [1 1] [1 1] [1 1]]"),
+            0.0,
+            false
+        ),
+        Err(JError::SingularMatrixErr)
+    ));
+
+    let y = Series::new("y".into(), [Some(1i64), Some(3), None, Some(7)]);
+    let xs = [Series::new("x".into(), [0i64, 1, 2, 3])];
+    let J::Dict(dict) = matrix::lsq_series(&y, &xs, 0.0, true).unwrap().into_j() else {
+        panic!("expected a dict")
+    };
+    assert_eq!(dict.keys().collect::<Vec<_>>(), ["coef", "residuals", "r2"]);
+    // no intercept, (3 + 21) / (1 + 9) without the null row
+    let J::Series(coef) = &dict["coef"] else {
+        panic!("expected a series")
+    };
+    assert!((coef.f64().unwrap().get(0).unwrap() - 2.4).abs() < 1e-9);
+}
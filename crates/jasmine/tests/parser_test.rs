@@ -1,4 +1,4 @@
-use jasmine::{JParser, Rule};
+use jasmine::{j::J, parse, parse_with_options, AstNode, JParser, ParseOptions, Rule};
 use pest::Parser;
 
 use crate::util::pretty_format_rules;
@@ -538,3 +538,897 @@ fn parse_case07() {
         actual
     )
 }
+
+#[test]
+fn parse_case08() {
+    let code = "
+    select from (select sym, qty from t) where sym in (select sym from watchlist);
+    select from t where qty > (select max qty from t), sym not in (select sym from blacklist);
+    ";
+    let pairs = match JParser::parse(Rule::Program, code) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("{}", e);
+            panic!("failed to parse")
+        }
+    };
+    let binding = pretty_format_rules(pairs);
+    let actual: Vec<&str> = binding.split("\n").collect();
+    assert_eq!(
+        vec![
+            "Exp -> SqlExp",
+            "   -> SelectOp",
+            "   -> FromExp -> SubQuery -> SqlExp",
+            "         -> SelectOp",
+            "           -> SeriesExp -> Id",
+            "           -> SeriesExp -> Id",
+            "         -> FromExp -> Id",
+            "   -> FilterExp -> InSubQueryExp",
+            "       -> Id",
+            "       -> InOp",
+            "       -> SubQuery -> SqlExp",
+            "           -> SelectOp -> SeriesExp -> Id",
+            "           -> FromExp -> Id",
+            "Exp -> SqlExp",
+            "   -> SelectOp",
+            "   -> FromExp -> Id",
+            "   -> FilterExp",
+            "     -> BinarySqlExp",
+            "       -> Id",
+            "       -> BinaryOp",
+            "       -> SubQuery -> SqlExp",
+            "           -> SelectOp -> SeriesExp -> UnarySqlExp",
+            "                 -> Id",
+            "                 -> Id",
+            "           -> FromExp -> Id",
+            "     -> InSubQueryExp",
+            "       -> Id",
+            "       -> InOp",
+            "       -> SubQuery -> SqlExp",
+            "           -> SelectOp -> SeriesExp -> Id",
+            "           -> FromExp -> Id",
+            "EOI",
+            "",
+        ],
+        actual
+    )
+}
+
+#[test]
+fn parse_sub_query() {
+    let nodes = parse(
+        "select from t where sym not in (select sym from watchlist)",
+        0,
+    )
+    .unwrap();
+    if let AstNode::Sql { filters, .. } = &nodes[0] {
+        if let AstNode::SubQuery { kind, exp, sql, .. } = &filters[0] {
+            assert_eq!(kind, "not in");
+            assert!(matches!(**exp, AstNode::Id { .. }));
+            assert!(matches!(**sql, AstNode::Sql { .. }));
+        } else {
+            panic!("expected subquery, got {:?}", filters[0])
+        }
+    } else {
+        panic!("expected sql, got {:?}", nodes[0])
+    }
+
+    let nodes = parse("select from (select sym from t)", 0).unwrap();
+    if let AstNode::Sql { from, .. } = &nodes[0] {
+        assert!(matches!(**from, AstNode::Sql { .. }));
+    }
+
+    assert!(parse(
+        "select from t where qty > (select max qty by sym from t)",
+        0
+    )
+    .is_err());
+    assert!(parse("select from t where sym in (select from watchlist)", 0).is_err());
+    assert!(parse("select from t where sym in (delete qty from watchlist)", 0).is_err());
+    assert!(parse(
+        "select from t where qty > (select max qty dyn 1D, date from t)",
+        0
+    )
+    .is_err());
+    assert!(parse(
+        "select from t where date in (select dyn 1D, date from t)",
+        0
+    )
+    .is_err());
+}
+
+#[test]
+fn parse_case09() {
+    let code = "
+    with a as (select from t where qty > 0), b as (select sum qty by sym from a) select from b;
+    ";
+    let pairs = match JParser::parse(Rule::Program, code) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("{}", e);
+            panic!("failed to parse")
+        }
+    };
+    let binding = pretty_format_rules(pairs);
+    let actual: Vec<&str> = binding.split("\n").collect();
+    assert_eq!(
+        vec![
+            "Exp -> WithExp",
+            "   -> CteExp",
+            "     -> SeriesName",
+            "     -> SqlExp",
+            "       -> SelectOp",
+            "       -> FromExp -> Id",
+            "       -> FilterExp -> BinarySqlExp",
+            "           -> Id",
+            "           -> BinaryOp",
+            "           -> Integer",
+            "   -> CteExp",
+            "     -> SeriesName",
+            "     -> SqlExp",
+            "       -> SelectOp -> SeriesExp -> UnarySqlExp",
+            "             -> Id",
+            "             -> Id",
+            "       -> GroupExp -> SeriesExp -> Id",
+            "       -> FromExp -> Id",
+            "   -> SqlExp",
+            "     -> SelectOp",
+            "     -> FromExp -> Id",
+            "EOI",
+            "",
+        ],
+        actual
+    )
+}
+
+#[test]
+fn parse_with() {
+    let nodes = parse(
+        "with a as (select from t), b as (select from a) select from b",
+        0,
+    )
+    .unwrap();
+    if let AstNode::With { names, sqls, .. } = &nodes[0] {
+        assert_eq!(names, &vec!["a".to_owned(), "b".to_owned()]);
+        assert_eq!(sqls.len(), 2);
+    } else {
+        panic!("expected with, got {:?}", nodes[0])
+    }
+
+    let err = parse(
+        "with a as (select from t), a as (select from t) select from a",
+        0,
+    )
+    .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("duplicate common table expression name: a"));
+
+    let err = parse(
+        "with a as (select from b), b as (select from t) select from a",
+        0,
+    )
+    .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("common table expression 'a' cannot refer to 'b' before its definition"));
+
+    let err = parse(
+        "with a as (select from t where sym in (select sym from a)) select from a",
+        0,
+    )
+    .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("common table expression 'a' cannot refer to 'a' before its definition"));
+}
+
+#[test]
+fn parse_explain() {
+    let nodes = parse("explain select sum qty dyn 10D, date, sym from t", 0).unwrap();
+    if let AstNode::Explain { sql, start, .. } = &nodes[0] {
+        assert!(matches!(sql.as_ref(), AstNode::Sql { .. }));
+        assert_eq!(*start, 0);
+    } else {
+        panic!("expected explain, got {:?}", nodes[0])
+    }
+
+    let nodes = parse("explain with a as (select from t) select from a", 0).unwrap();
+    assert!(matches!(
+        &nodes[0],
+        AstNode::Explain { sql, .. } if matches!(sql.as_ref(), AstNode::With { .. })
+    ));
+
+    assert!(parse("explain t", 0).is_err());
+}
+
+#[test]
+fn parse_pivot() {
+    let nodes = parse("pivot sum qty by date for sym from t where qty > 0", 0).unwrap();
+    if let AstNode::Pivot {
+        kind,
+        sql,
+        index,
+        name,
+        ..
+    } = &nodes[0]
+    {
+        assert_eq!(kind, "pivot");
+        assert_eq!(*index, 1);
+        assert_eq!(name, "sym");
+        if let AstNode::Sql {
+            groups,
+            ops,
+            filters,
+            ..
+        } = sql.as_ref()
+        {
+            assert_eq!(groups.len(), 2);
+            assert_eq!(ops.len(), 1);
+            assert_eq!(filters.len(), 1);
+        } else {
+            panic!("expected sql, got {:?}", sql)
+        }
+    } else {
+        panic!("expected pivot, got {:?}", nodes[0])
+    }
+
+    let nodes = parse("unpivot qty, price by date, sym from t", 0).unwrap();
+    if let AstNode::Pivot {
+        kind,
+        sql,
+        index,
+        name,
+        ..
+    } = &nodes[0]
+    {
+        assert_eq!(kind, "unpivot");
+        assert_eq!(*index, 2);
+        assert_eq!(name, "variable");
+        assert!(
+            matches!(sql.as_ref(), AstNode::Sql { ops, groups, .. } if ops.len() == 4 && groups.is_empty())
+        );
+    } else {
+        panic!("expected unpivot, got {:?}", nodes[0])
+    }
+
+    let err = parse("pivot sum qty by date for sym, exch from t", 0).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("'pivot' requires a single series name for 'for', got 'sym, exch'"));
+
+    let err = parse("pivot sum qty by date for upper sym from t", 0).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("'pivot' requires a single series name for 'for', got 'upper sym'"));
+
+    let err = parse("pivot sum qty for sym from t", 0).unwrap_err();
+    assert!(err.to_string().contains("'pivot' requires 'by'"));
+}
+
+#[test]
+fn parse_insert() {
+    let nodes = parse("insert into t df[sym=`a`b, qty=1 2]", 0).unwrap();
+    if let AstNode::Insert {
+        kind,
+        table,
+        keys,
+        exp,
+        ..
+    } = &nodes[0]
+    {
+        assert_eq!(kind, "insert");
+        assert_eq!(table, "t");
+        assert!(keys.is_empty());
+        assert!(matches!(exp.as_ref(), AstNode::J(J::DataFrame(_))));
+    } else {
+        panic!("expected insert, got {:?}", nodes[0])
+    }
+
+    let nodes = parse(
+        "upsert into t on sym, date df[sym=`a, date=2024-01-01, qty]",
+        0,
+    )
+    .unwrap();
+    if let AstNode::Insert { kind, keys, .. } = &nodes[0] {
+        assert_eq!(kind, "upsert");
+        assert_eq!(keys, &vec!["sym".to_owned(), "date".to_owned()]);
+    } else {
+        panic!("expected upsert, got {:?}", nodes[0])
+    }
+
+    let nodes = parse("upsert into t on sym (select from u where qty > 0)", 0).unwrap();
+    assert!(matches!(
+        &nodes[0],
+        AstNode::Insert { exp, .. } if matches!(exp.as_ref(), AstNode::Sql { .. })
+    ));
+
+    let err = parse("upsert into t on sym, date df[sym=`a, qty=1]", 0).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("key column 'date' does not exist in dataframe"));
+
+    let err = parse("insert into t df[sym=`a, 1 + 2]", 0).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("'insert' requires a column name, got '1 + 2'"));
+
+    let err = parse("upsert into t df[sym=`a]", 0).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("'upsert' requires key columns by 'on'"));
+
+    let err = parse("insert into t on sym df[sym=`a]", 0).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("'insert' does not take key columns"));
+}
+
+#[test]
+fn parse_distinct_take_skip() {
+    let nodes = parse("select distinct sym, date from t take 10 skip 100", 0).unwrap();
+    if let AstNode::Sql {
+        ops,
+        take,
+        take_by,
+        skip,
+        distinct,
+        ..
+    } = &nodes[0]
+    {
+        assert!(distinct);
+        assert_eq!(ops.len(), 2);
+        assert_eq!(take.as_ref(), &AstNode::J(J::I64(10)));
+        assert!(take_by.is_empty());
+        assert_eq!(skip.as_ref(), &AstNode::J(J::I64(100)));
+    } else {
+        panic!("expected sql, got {:?}", nodes[0])
+    }
+
+    let nodes = parse("select from t take n by sym, date", 0).unwrap();
+    if let AstNode::Sql {
+        take,
+        take_by,
+        skip,
+        distinct,
+        ..
+    } = &nodes[0]
+    {
+        assert!(!distinct);
+        assert!(matches!(take.as_ref(), AstNode::Id { name, .. } if name == "n"));
+        assert_eq!(take_by.len(), 2);
+        assert_eq!(skip.as_ref(), &AstNode::J(J::Null));
+    } else {
+        panic!("expected sql, got {:?}", nodes[0])
+    }
+
+    assert!(parse("select distinct from t skip 1", 0).is_ok());
+    assert!(parse("update distinct qty from t", 0).is_err());
+
+    // keywords as prefixes of series names
+    let nodes = parse(
+        "select distinct_count, distinctive from t take 1 skip skip_n",
+        0,
+    )
+    .unwrap();
+    if let AstNode::Sql {
+        ops,
+        skip,
+        distinct,
+        ..
+    } = &nodes[0]
+    {
+        assert!(!distinct);
+        let names: Vec<&str> = ops
+            .iter()
+            .map(|op| match op {
+                AstNode::Id { name, .. } => name.as_str(),
+                op => panic!("expected id, got {:?}", op),
+            })
+            .collect();
+        assert_eq!(names, ["distinct_count", "distinctive"]);
+        assert!(matches!(skip.as_ref(), AstNode::Id { name, .. } if name == "skip_n"));
+    } else {
+        panic!("expected sql, got {:?}", nodes[0])
+    }
+    assert!(parse("select from t skipped", 0).is_err());
+}
+
+#[test]
+fn parse_set_op() {
+    let nodes = parse("select from t union all select from u", 0).unwrap();
+    if let AstNode::SetOp { op, lhs, rhs, .. } = &nodes[0] {
+        assert_eq!(op, "union all");
+        assert!(matches!(lhs.as_ref(), AstNode::Sql { .. }));
+        assert!(matches!(rhs.as_ref(), AstNode::Sql { .. }));
+    } else {
+        panic!("expected set op, got {:?}", nodes[0])
+    }
+
+    let nodes = parse(
+        "select sym from t where qty > n intersect (select sym from u union select sym from v) except df[sym=`a]",
+        0,
+    )
+    .unwrap();
+    if let AstNode::SetOp { op, lhs, rhs, .. } = &nodes[0] {
+        assert_eq!(op, "except");
+        assert!(matches!(rhs.as_ref(), AstNode::J(J::DataFrame(_))));
+        assert!(matches!(
+            lhs.as_ref(),
+            AstNode::SetOp { op, rhs, .. } if op == "intersect" && matches!(rhs.as_ref(), AstNode::SetOp { .. })
+        ));
+    } else {
+        panic!("expected set op, got {:?}", nodes[0])
+    }
+
+    // 'intersect' binds tighter than 'union' and 'except', the same as ansi sql
+    let nodes = parse(
+        "select from t except select from u intersect select from v union select from w",
+        0,
+    )
+    .unwrap();
+    if let AstNode::SetOp { op, lhs, rhs, .. } = &nodes[0] {
+        assert_eq!(op, "union");
+        assert!(matches!(rhs.as_ref(), AstNode::Sql { .. }));
+        if let AstNode::SetOp { op, lhs, rhs, .. } = lhs.as_ref() {
+            assert_eq!(op, "except");
+            assert!(matches!(lhs.as_ref(), AstNode::Sql { .. }));
+            assert!(matches!(rhs.as_ref(), AstNode::SetOp { op, .. } if op == "intersect"));
+        } else {
+            panic!("expected set op, got {:?}", lhs)
+        }
+    } else {
+        panic!("expected set op, got {:?}", nodes[0])
+    }
+    let ansi = jasmine::parse_ansi_sql(
+        "select * from t except select * from u intersect select * from v union select * from w",
+        0,
+    )
+    .unwrap();
+    fn shape(node: &AstNode) -> String {
+        match node {
+            AstNode::SetOp { op, lhs, rhs, .. } => {
+                format!("({} {} {})", shape(lhs), op, shape(rhs))
+            }
+            _ => "_".to_owned(),
+        }
+    }
+    assert_eq!(shape(&nodes[0]), shape(&ansi));
+
+    let nodes = parse("select from t take n except select from u", 0).unwrap();
+    assert!(matches!(&nodes[0], AstNode::SetOp { op, .. } if op == "except"));
+
+    // still valid names outside of set operations
+    let nodes = parse("union(a, b); f intersect", 0).unwrap();
+    assert!(matches!(&nodes[0], AstNode::Call { .. }));
+    assert!(matches!(&nodes[1], AstNode::UnaryOp { .. }));
+
+    let err = parse("df[sym=`a, qty=1] union all df[sym=`b]", 0).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("'union all' requires the same number of columns, got 2 and 1"));
+}
+
+#[test]
+fn parse_param() {
+    let nodes = parse("select from t where qty > $1, sym == $sym take $n", 0).unwrap();
+    if let AstNode::Sql { filters, take, .. } = &nodes[0] {
+        assert!(matches!(
+            &filters[0],
+            AstNode::BinOp { rhs, .. } if matches!(rhs.as_ref(), AstNode::Param { name, start: 26, end: 28, .. } if name == "1")
+        ));
+        assert!(matches!(take.as_ref(), AstNode::Param { name, .. } if name == "n"));
+    } else {
+        panic!("expected sql, got {:?}", nodes[0])
+    }
+
+    let nodes = parse("f($1, $x)", 0).unwrap();
+    assert!(matches!(
+        &nodes[0],
+        AstNode::Call { args, .. } if matches!(&args[1], AstNode::Param { name, .. } if name == "x")
+    ));
+
+    // '$' after an operand is still the cast operator
+    let nodes = parse("t$date; `int$x", 0).unwrap();
+    assert!(
+        matches!(&nodes[0], AstNode::BinOp { op, .. } if matches!(op.as_ref(), AstNode::Op { name, .. } if name == "$"))
+    );
+    assert!(matches!(&nodes[1], AstNode::BinOp { .. }));
+}
+
+#[test]
+fn parse_int_literals() {
+    let nodes = parse(
+        "0xFF; -0b1010; 1_000_000; 1_000.5; 0b; 0x7fff_ffff_ffff_ffff",
+        0,
+    )
+    .unwrap();
+    assert_eq!(nodes[0], AstNode::J(J::I64(255)));
+    assert_eq!(nodes[1], AstNode::J(J::I64(-10)));
+    assert_eq!(nodes[2], AstNode::J(J::I64(1_000_000)));
+    assert_eq!(nodes[3], AstNode::J(J::F64(1_000.5)));
+    assert_eq!(nodes[4], AstNode::J(J::Boolean(false)));
+    assert_eq!(nodes[5], AstNode::J(J::I64(i64::MAX)));
+
+    let nodes = parse("0xFFu8 0x10 0b1_1; 0b1b 0b; 1_000i32 2_000; 0b11f64 1.5", 0).unwrap();
+    let series = |node: &AstNode| match node {
+        AstNode::J(J::Series(s)) => s.clone(),
+        _ => panic!("expected series, got {:?}", node),
+    };
+    let s = series(&nodes[0]);
+    assert_eq!(s.u8().unwrap().to_vec(), [Some(255), Some(16), Some(3)]);
+    let s = series(&nodes[1]);
+    assert_eq!(
+        s.bool().unwrap().into_iter().collect::<Vec<_>>(),
+        [Some(false), Some(true), Some(false)]
+    );
+    let s = series(&nodes[2]);
+    assert_eq!(s.i32().unwrap().to_vec(), [Some(1000), Some(2000)]);
+    let s = series(&nodes[3]);
+    assert_eq!(s.f64().unwrap().to_vec(), [Some(3.0), Some(1.5)]);
+
+    let err = parse("0x8000_0000_0000_0000", 0).unwrap_err();
+    assert!(err.to_string().contains(
+        "'0x8000_0000_0000_0000' is out of range for i64, [-9223372036854775808, 9223372036854775807]"
+    ));
+    let err = parse("1u8 256", 0).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("'256' is out of range for u8, [0, 255]"));
+    let err = parse("-1u16 2", 0).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("'-1' is out of range for u16, [0, 65535]"));
+
+    // scalars are i64 or f64, other suffixes are for series
+    let nodes = parse("0x10i64; -1_000i64; 0b11f64", 0).unwrap();
+    assert_eq!(nodes[0], AstNode::J(J::I64(16)));
+    assert_eq!(nodes[1], AstNode::J(J::I64(-1000)));
+    assert_eq!(nodes[2], AstNode::J(J::F64(3.0)));
+    let err = parse("0xFFu8", 0).unwrap_err();
+    assert!(err.to_string().contains(
+        "'0xFFu8': scalars are i64 or f64, 'u8' is only supported in series literals, e.g. '0xFFu8 0'"
+    ));
+    let err = parse("x + 1i32", 0).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("'i32' is only supported in series literals"));
+}
+
+#[test]
+fn parse_float_series() {
+    let f64s = |code: &str| match parse(code, 0).unwrap().pop().unwrap() {
+        AstNode::J(J::Series(s)) => s.f64().unwrap().to_vec(),
+        node => panic!("expected f64 series, got {:?}", node),
+    };
+    let f32s = |code: &str| match parse(code, 0).unwrap().pop().unwrap() {
+        AstNode::J(J::Series(s)) => s.f32().unwrap().to_vec(),
+        node => panic!("expected f32 series, got {:?}", node),
+    };
+    let cases = [
+        ("1e-3 2.5e2", vec![Some(1e-3), Some(2.5e2)]),
+        (
+            "1.0 inf -inf",
+            vec![Some(1.0), Some(f64::INFINITY), Some(f64::NEG_INFINITY)],
+        ),
+        (
+            "inf 1.5E+1 .5e1",
+            vec![Some(f64::INFINITY), Some(15.0), Some(5.0)],
+        ),
+        ("1 2.5e2 0n", vec![Some(1.0), Some(250.0), None]),
+        ("-1e3 1_000.5", vec![Some(-1000.0), Some(1000.5)]),
+        ("2e0f64 3", vec![Some(2.0), Some(3.0)]),
+    ];
+    for (code, expected) in cases {
+        assert_eq!(f64s(code), expected, "{}", code);
+    }
+    assert_eq!(f32s("1e2f32 inf"), vec![Some(100.0), Some(f32::INFINITY)]);
+    assert_eq!(
+        f32s("inff32 -1.5e-1"),
+        vec![Some(f32::INFINITY), Some(-0.15)]
+    );
+
+    let nans = f64s("nan 1.0 -nan");
+    assert!(nans[0].unwrap().is_nan() && nans[2].unwrap().is_nan());
+    assert!(f32s("nanf32 1")[0].unwrap().is_nan());
+    assert!(matches!(&parse("nan", 0).unwrap()[0], AstNode::J(J::F64(f)) if f.is_nan()));
+
+    // names starting with 'inf' and 'nan' are still ids
+    let nodes = parse("info; nano", 0).unwrap();
+    assert!(matches!(&nodes[0], AstNode::Id { name, .. } if name == "info"));
+    assert!(matches!(&nodes[1], AstNode::Id { name, .. } if name == "nano"));
+
+    let nodes = parse("1.5f64; -inff64; 2.5e2f64", 0).unwrap();
+    assert_eq!(nodes[0], AstNode::J(J::F64(1.5)));
+    assert_eq!(nodes[1], AstNode::J(J::F64(f64::NEG_INFINITY)));
+    assert_eq!(nodes[2], AstNode::J(J::F64(250.0)));
+    let err = parse("inff32", 0).unwrap_err();
+    assert!(err.to_string().contains(
+        "'inff32': scalars are i64 or f64, 'f32' is only supported in series literals, e.g. 'inff32 0'"
+    ));
+}
+
+#[test]
+fn parse_typed_null() {
+    let series = |code: &str| match parse(code, 0).unwrap().pop().unwrap() {
+        AstNode::J(J::Series(s)) => s,
+        node => panic!("expected series, got {:?}", node),
+    };
+    let cases = [
+        ("0Ni64 0Ni64", "i64", 2),
+        ("0Nf64 0n 0Nf64", "f64", 3),
+        ("0Nd 0Nd", "date", 2),
+        ("0Ns 0n", "cat", 2),
+        ("0Nstr 0Nstr", "str", 2),
+        ("0Nu8 1u8", "u8", 1),
+        ("1 0Nf64", "f64", 1),
+        ("2024-01-01 0Nd", "date", 1),
+    ];
+    for (code, dtype, null_count) in cases {
+        let s = series(code);
+        assert_eq!(s.dtype().to_string(), dtype, "{}", code);
+        assert_eq!(s.null_count(), null_count, "{}", code);
+    }
+    assert!(series("0Np 0Np")
+        .dtype()
+        .to_string()
+        .starts_with("datetime[ns"));
+    assert!(series("0n 0n").dtype().is_null());
+
+    let nodes = parse("0Ni32; 0Nd; 0n", 0).unwrap();
+    assert!(matches!(&nodes[0], AstNode::J(J::TypedNull(dtype)) if dtype.to_string() == "i32"));
+    assert!(matches!(&nodes[1], AstNode::J(J::TypedNull(dtype)) if dtype.to_string() == "date"));
+    assert_eq!(nodes[2], AstNode::J(J::Null));
+
+    let err = parse("1 0Nd", 0).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("'0Nd' is a null of date, got a series of i64"));
+}
+
+#[test]
+fn parse_nested_series() {
+    let df = match parse(
+        "df[l = [[1, 2], [3], null], m = [[1, 2.5], [0n], [4.0]], s = {a: 1 2 3, b: `x`y`z, c: 0.5}]",
+        0,
+    )
+    .unwrap()
+    .pop()
+    .unwrap()
+    {
+        AstNode::J(J::DataFrame(df)) => df,
+        node => panic!("expected df, got {:?}", node),
+    };
+    let dtypes = df
+        .dtypes()
+        .iter()
+        .map(|dtype| dtype.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(dtypes, vec!["list[i64]", "list[f64]", "struct[3]"]);
+    assert_eq!(df.column("l").unwrap().null_count(), 1);
+    let fields = df
+        .column("s")
+        .unwrap()
+        .struct_()
+        .unwrap()
+        .fields_as_series()
+        .iter()
+        .map(|s| format!("{}: {}", s.name(), s.dtype()))
+        .collect::<Vec<_>>();
+    assert_eq!(fields, vec!["a: i64", "b: cat", "c: f64"]);
+
+    let nested = J::MixedList(vec![
+        J::MixedList(vec![J::I64(1), J::I64(2)]),
+        J::MixedList(vec![J::I64(3)]),
+    ]);
+    let nested = J::MixedList(vec![nested.clone(), nested]);
+    assert_eq!(
+        nested.into_series().unwrap().dtype().to_string(),
+        "list[list[i64]]"
+    );
+
+    // outside of dataframes, nested lists and dicts stay lists and dicts
+    let nodes = parse("[[1, 2], [3]]; {a: 1 2, b: `x`y}", 0).unwrap();
+    assert!(matches!(&nodes[0], AstNode::J(J::MixedList(_))));
+    assert!(matches!(&nodes[1], AstNode::J(J::Dict(_))));
+
+    let err = parse("df[l = [[1, 2], [`a]]]", 0).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("requires list entries of the same data type, got i64 and cat"));
+}
+
+#[test]
+fn parse_zoned_datetime() {
+    let j = |code: &str| parse(code, 0).unwrap().pop().unwrap();
+    let utc_ms = 1704067200000;
+    let cases = [
+        ("2024-01-01T09:00:00+09:00", "UTC"),
+        ("2024-01-01T00:00:00Z", "UTC"),
+        ("2023-12-31T19:00:00.000-05:00", "UTC"),
+        ("2024-01-01T09:00:00@Asia/Tokyo", "Asia/Tokyo"),
+        ("2024-01-01T@Europe/London", "Europe/London"),
+    ];
+    for (code, tz) in cases {
+        assert_eq!(
+            j(code),
+            AstNode::J(J::Datetime {
+                ms: utc_ms,
+                timezone: tz.to_owned()
+            }),
+            "{}",
+            code
+        );
+    }
+    assert_eq!(
+        j("2024-01-01D09:00:00@Asia/Tokyo"),
+        AstNode::J(J::Timestamp {
+            ns: utc_ms * 1_000_000,
+            timezone: "Asia/Tokyo".to_owned()
+        })
+    );
+
+    let AstNode::J(J::Series(s)) = j("2024-01-01D09:00:00@Asia/Tokyo 0n 2024-01-01D+09:00") else {
+        panic!("expected a series")
+    };
+    assert_eq!(s.dtype().to_string(), "datetime[ns, Asia/Tokyo]");
+    let ns = s.to_physical_repr().i64().unwrap().to_vec();
+    assert_eq!(
+        ns,
+        vec![
+            Some(utc_ms * 1_000_000),
+            None,
+            Some((utc_ms - 32_400_000) * 1_000_000)
+        ]
+    );
+
+    let err = parse("2024-01-01T09:00:00@Mars/Olympus", 0).unwrap_err();
+    assert!(err.to_string().contains("Unknown timezone 'Mars/Olympus'"));
+}
+
+#[test]
+fn parse_with_parse_options() {
+    let opts = ParseOptions {
+        timezone: "Asia/Tokyo".to_owned(),
+        source_id: 3,
+        ..Default::default()
+    };
+    let nodes =
+        parse_with_options("2024-01-01T09:00:00; 2024-01-01D09:00:00 0n; x", &opts).unwrap();
+    assert_eq!(
+        nodes[0],
+        AstNode::J(J::Datetime {
+            ms: 1704067200000,
+            timezone: "Asia/Tokyo".to_owned()
+        })
+    );
+    let AstNode::J(J::Series(s)) = &nodes[1] else {
+        panic!("expected a series")
+    };
+    assert_eq!(s.dtype().to_string(), "datetime[ns, Asia/Tokyo]");
+    assert_eq!(
+        s.to_physical_repr().i64().unwrap().get(0),
+        Some(1704067200000000000)
+    );
+    assert!(matches!(&nodes[2], AstNode::Id { source_id: 3, .. }));
+
+    // UTC by default
+    let nodes = parse_with_options("2024-01-01D", &ParseOptions::default()).unwrap();
+    assert_eq!(
+        nodes[0],
+        AstNode::J(J::Timestamp {
+            ns: 1704067200000000000,
+            timezone: "UTC".to_owned()
+        })
+    );
+
+    let disabled = ParseOptions {
+        params: false,
+        sql: false,
+        ..Default::default()
+    };
+    let cases = [
+        ("select from t where a==1", "sql is disabled"),
+        ("f($1)", "parameters are disabled"),
+        ("df[a=1 2] union df[a=3 4]", "sql is disabled"),
+    ];
+    for (code, err) in cases {
+        let e = parse_with_options(code, &disabled).unwrap_err();
+        assert!(e.to_string().contains(err), "{}: {}", code, e);
+    }
+
+    let unknown = ParseOptions {
+        timezone: "Mars/Olympus".to_owned(),
+        ..Default::default()
+    };
+    let e = parse_with_options("2024-01-01T", &unknown).unwrap_err();
+    assert!(e.to_string().contains("Unknown timezone 'Mars/Olympus'"));
+}
+
+#[test]
+fn parse_calendar_duration() {
+    let j = |code: &str| parse(code, 0).unwrap().pop().unwrap();
+    let period = |months, days| {
+        AstNode::J(J::Period {
+            months,
+            days,
+            ns: 0,
+        })
+    };
+    assert_eq!(j("1mo"), period(1, 0));
+    assert_eq!(j("2q"), period(6, 0));
+    assert_eq!(j("-1y"), period(-12, 0));
+    assert_eq!(j("3d"), period(0, 3));
+    assert_eq!(j("1w"), period(0, 7));
+    assert_eq!(j("1ms"), AstNode::J(J::Duration(1_000_000)));
+    assert_eq!(j("1us"), AstNode::J(J::Duration(1_000)));
+    assert_eq!(j("1m"), AstNode::J(J::Duration(60_000_000_000)));
+
+    let AstNode::J(J::Series(s)) = j("1ms 2us 0n") else {
+        panic!("expected a series")
+    };
+    assert_eq!(s.dtype().to_string(), "duration[ns]");
+    assert_eq!(
+        s.to_physical_repr().i64().unwrap().to_vec(),
+        vec![Some(1_000_000), Some(2_000), None]
+    );
+
+    let AstNode::J(J::Series(s)) = j("1mo 1w 1h") else {
+        panic!("expected a series")
+    };
+    let fields = s.struct_().unwrap().fields_as_series();
+    let fields = fields
+        .iter()
+        .map(|s| {
+            s.to_physical_repr()
+                .cast(&polars::prelude::DataType::Int64)
+                .unwrap()
+        })
+        .map(|s| s.i64().unwrap().to_vec())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        fields,
+        vec![
+            vec![Some(1), Some(0), Some(0)],
+            vec![Some(0), Some(7), Some(0)],
+            vec![Some(0), Some(0), Some(3_600_000_000_000)],
+        ]
+    );
+}
+
+#[test]
+fn parse_month_quarter_week() {
+    let j = |code: &str| parse(code, 0).unwrap().pop().unwrap();
+    assert_eq!(j("1970-01m"), AstNode::J(J::Month(0)));
+    assert_eq!(j("2024-03m"), AstNode::J(J::Month(650)));
+    assert_eq!(j("1969-12m"), AstNode::J(J::Month(-1)));
+    assert_eq!(j("2024Q1"), AstNode::J(J::Quarter(216)));
+    assert_eq!(j("1970W01"), AstNode::J(J::Week(0)));
+    assert_eq!(j("2024W10"), AstNode::J(J::Week(2827)));
+
+    // the first days, 2024.03.01, 2024.01.01 and 2024.03.04
+    assert_eq!(J::Month(650).first_day(), Some(19783));
+    assert_eq!(J::Quarter(216).first_day(), Some(19723));
+    assert_eq!(J::Week(2827).first_day(), Some(19786));
+    assert_eq!(J::Month(-1).first_day(), Some(-31));
+
+    let AstNode::J(J::Series(s)) = j("2024-01m 0n 2024-03m") else {
+        panic!("expected a series")
+    };
+    assert_eq!(s.dtype().to_string(), "date");
+    assert_eq!(
+        s.to_physical_repr().i32().unwrap().to_vec(),
+        vec![Some(19723), None, Some(19783)]
+    );
+    let AstNode::J(J::Series(s)) = j("2024Q1 2024Q2") else {
+        panic!("expected a series")
+    };
+    assert_eq!(
+        s.to_physical_repr().i32().unwrap().to_vec(),
+        vec![Some(19723), Some(19814)]
+    );
+
+    for code in ["2024-13m", "2024Q5", "2024W54"] {
+        assert!(parse(code, 0).is_err(), "{}", code);
+    }
+}
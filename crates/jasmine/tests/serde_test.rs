@@ -0,0 +1,36 @@
+#![cfg(feature = "serde")]
+
+use jasmine::{from_json, j::J, parse, to_json, AstNode};
+use ndarray::array;
+
+#[test]
+fn serde_ast_nodes() {
+    let code = "
+    f = fn(x, y) { x + y * 2 };
+    t = df[sym=`a`b, qty=1 2, price=1.5 0n];
+    select sum qty, avg price by sym from t where qty > 1;
+    [2024-03m, 1mo, 0Ni64, {a: 2024-01-01D09:00:00@Asia/Tokyo}]
+    ";
+    let nodes = parse(code, 3).unwrap();
+    let json = to_json(&nodes).unwrap();
+    let decoded: Vec<AstNode> = from_json(&json).unwrap();
+    assert_eq!(decoded, nodes);
+}
+
+#[test]
+fn serde_j() {
+    let j = parse("df[sym=`a`b`a, qty=1 2 0n]", 0)
+        .unwrap()
+        .pop()
+        .unwrap()
+        .as_j()
+        .unwrap();
+    let decoded: J = from_json(&to_json(&j).unwrap()).unwrap();
+    assert_eq!(decoded, j);
+
+    let j = J::Matrix(array![[1.0, 2.0], [3.0, 4.0]].into_shared());
+    let decoded: J = from_json(&to_json(&j).unwrap()).unwrap();
+    assert_eq!(decoded, j);
+
+    assert!(from_json::<J>("{\"I64\": \"1\"}").is_err());
+}
@@ -0,0 +1,75 @@
+use jasmine::{
+    j::J,
+    parse,
+    visit::{fold_children, walk_node, walk_node_mut},
+    AstNode, Fold, Visitor, VisitorMut,
+};
+
+#[derive(Default)]
+struct Ids(Vec<String>);
+
+impl Visitor for Ids {
+    fn visit_node(&mut self, node: &AstNode) {
+        if let AstNode::Id { name, .. } = node {
+            self.0.push(name.to_owned())
+        }
+        walk_node(self, node)
+    }
+}
+
+struct Rename;
+
+impl VisitorMut for Rename {
+    fn visit_node_mut(&mut self, node: &mut AstNode) {
+        if let AstNode::Id { name, .. } = node {
+            name.push('2')
+        }
+        walk_node_mut(self, node)
+    }
+}
+
+struct Double;
+
+impl Fold for Double {
+    fn fold_j(&mut self, j: J) -> jasmine::errors::JResult<AstNode> {
+        match j {
+            J::I64(i) => Ok(AstNode::J(J::I64(i * 2))),
+            j => Ok(AstNode::J(j)),
+        }
+    }
+}
+
+#[test]
+fn visit_ast_nodes() {
+    let code = "
+    select s = sum qty by sym from t where price > p sort -ts take n;
+    try { raise a } catch(err) { b };
+    f = fn(x) { [x, y] };
+    ";
+    let nodes = parse(code, 0).unwrap();
+    let mut ids = Ids::default();
+    nodes.iter().for_each(|node| ids.visit_node(node));
+    assert_eq!(
+        ids.0,
+        ["t", "price", "p", "sym", "sum", "qty", "-ts", "n", "a", "b", "x", "y"]
+    );
+
+    let mut nodes = nodes;
+    nodes
+        .iter_mut()
+        .for_each(|node| Rename.visit_node_mut(node));
+    let mut ids = Ids::default();
+    nodes.iter().for_each(|node| ids.visit_node(node));
+    assert!(ids.0.iter().all(|id| id.ends_with('2')));
+
+    let nodes = parse("x = [1, 2 * y]; select from t take 3", 0).unwrap();
+    let nodes = nodes
+        .into_iter()
+        .map(|node| fold_children(&mut Double, node))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        nodes,
+        parse("x = [2, 4 * y]; select from t take 6", 0).unwrap()
+    );
+}
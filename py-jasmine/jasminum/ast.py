@@ -9,23 +9,47 @@ with contextlib.suppress(ImportError):
         AstCall,
         AstDataFrame,
         AstDict,
+        AstExplain,
         AstFn,
         AstId,
         AstIf,
         AstIndexAssign,
+        AstInsert,
         AstList,
         AstMatrix,
         AstOp,
+        AstParam,
+        AstPivot,
         AstRaise,
         AstReturn,
         AstSeries,
+        AstSetOp,
         AstSkip,
         AstSql,
+        AstSubQuery,
         AstTry,
         AstUnaryOp,
         AstWhile,
+        AstWith,
         JObj,
+        SourceMap,
+        collect_sql,
+        explain_sql,
         get_timezone,
+        ipc_deserialize,
+        ipc_serialize,
+        matrix_broadcast,
+        matrix_det,
+        matrix_from_dataframe,
+        matrix_from_series,
+        matrix_inverse,
+        matrix_lsq,
+        matrix_mul,
+        matrix_solve,
+        matrix_to_dataframe,
+        matrix_to_series,
+        matrix_transpose,
+        parse_ansi_sql,
         parse_source_code,
         print_trace,
     )
@@ -53,6 +77,13 @@ class AstType(Enum):
     Series = 18
     Sql = 19
     Skip = 20
+    SubQuery = 21
+    With = 22
+    Explain = 23
+    Pivot = 24
+    Insert = 25
+    SetOp = 26
+    Param = 27
 
 
 def downcast_ast_node(node: Ast):
@@ -100,6 +131,20 @@ def downcast_ast_node(node: Ast):
             return node.sql()
         case AstType.Skip:
             return node.skip()
+        case AstType.SubQuery:
+            return node.sub_query()
+        case AstType.With:
+            return node.with_exp()
+        case AstType.Explain:
+            return node.explain()
+        case AstType.Pivot:
+            return node.pivot()
+        case AstType.Insert:
+            return node.insert()
+        case AstType.SetOp:
+            return node.set_op()
+        case AstType.Param:
+            return node.param()
 
 
 all = [
@@ -109,23 +154,47 @@ all = [
     AstCall,
     AstDataFrame,
     AstDict,
+    AstExplain,
     AstFn,
     AstId,
     AstIf,
     AstIndexAssign,
+    AstInsert,
     AstList,
     AstMatrix,
     AstOp,
+    AstParam,
+    AstPivot,
     AstRaise,
     AstReturn,
     AstSeries,
+    AstSetOp,
     AstSkip,
     AstSql,
+    AstSubQuery,
     AstTry,
     AstUnaryOp,
     AstWhile,
+    AstWith,
     JObj,
+    SourceMap,
+    collect_sql,
+    explain_sql,
+    parse_ansi_sql,
     parse_source_code,
     print_trace,
     get_timezone,
+    ipc_deserialize,
+    ipc_serialize,
+    matrix_broadcast,
+    matrix_det,
+    matrix_from_dataframe,
+    matrix_from_series,
+    matrix_inverse,
+    matrix_lsq,
+    matrix_mul,
+    matrix_solve,
+    matrix_to_dataframe,
+    matrix_to_series,
+    matrix_transpose,
 ]
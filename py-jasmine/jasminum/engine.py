@@ -6,9 +6,22 @@ from typing import Callable
 
 import polars as pl
 
-from . import cfg, debug, df, expr, io, iterator, math, series, sql, string, temporal
+from . import (
+    cfg,
+    debug,
+    df,
+    expr,
+    io,
+    iterator,
+    math,
+    matrix,
+    series,
+    sql,
+    string,
+    temporal,
+)
 from . import operator as op
-from .ast import get_timezone, print_trace
+from .ast import SourceMap, get_timezone, print_trace
 from .exceptions import JasmineEvalException
 from .j import J, JParted, JType
 from .j_fn import JFn
@@ -19,22 +32,23 @@ from .j_task import JTask
 class Engine:
     globals: dict[str, J]
     handles: dict[int, JHandle]
-    # source_id -> (source_code, filepath)
-    sources: dict[int, (str, str)]
-    # filepath -> source_id
-    source_paths: dict[str, int]
+    # id 0 is the source of interactive inputs, loaded files get ids from 1
+    sources: SourceMap
     builtins: dict[str, J]
     timer_task: asyncio.Task
     timer_tasks: dict[int, JTask]
     # start_pos, fn_body
     stack: list[JFn]
+    # of datetime and timestamp literals without a timezone
+    timezone: str
 
-    def __init__(self) -> None:
+    def __init__(self, timezone: str | None = None) -> None:
+        self.timezone = timezone or get_timezone()
         self.globals = dict()
         self.handles = dict()
-        self.sources = dict()
+        self.sources = SourceMap()
+        self.sources.add("", "")
         self.builtins = dict()
-        self.source_paths = dict()
         self.timer_tasks = dict()
         self.stack = []
 
@@ -131,6 +145,18 @@ class Engine:
         self.register_builtin("dt.today", temporal.today)
         self.register_builtin("dt.combine", temporal.combine)
 
+        # matrix
+        self.register_builtin("mx.from", matrix.to_matrix)
+        self.register_builtin("mx.df", matrix.to_df)
+        self.register_builtin("mx.series", matrix.to_series)
+        self.register_builtin("mx.mul", matrix.mul)
+        self.register_builtin("mx.t", matrix.transpose)
+        self.register_builtin("mx.inv", matrix.inv)
+        self.register_builtin("mx.det", matrix.det)
+        self.register_builtin("mx.solve", matrix.solve)
+        self.register_builtin("lsq", matrix.lsq)
+        self.register_builtin("ridge", matrix.ridge)
+
         # binary
         self.register_builtin("corr0", math.corr0)
         self.register_builtin("corr1", math.corr1)
@@ -289,11 +315,11 @@ class Engine:
     ) -> str:
         if source_id == -1:
             return msg
-        source, path = self.sources.get(source_id)
         if src:
+            _, path = self.sources.get_source(source_id)
             return print_trace(src, path, pos - fn_start, msg)
         else:
-            return print_trace(source, path, pos, msg)
+            return self.sources.trace(source_id, pos, msg)
 
     # YYYYMMDD_00
     # YYYY_00
@@ -350,22 +376,17 @@ class Engine:
     def remove_handle(self, handle_id: int) -> None:
         self.handles.pop(handle_id)
 
+    # (source_code, filepath)
     def get_source(self, source_id: int) -> tuple[str, str]:
-        return self.sources.get(source_id, ("", ""))
-
-    def set_source(self, source_id: int, source: tuple[str, str]) -> None:
-        self.sources[source_id] = source
-        filepath = source[0]
-        self.source_paths[filepath] = source_id
+        return self.sources.get_source(source_id)
 
-    def get_max_source_id(self) -> int:
-        if len(self.sources) == 0:
-            return 1
-        else:
-            return max(self.sources.keys()) + 1
+    def set_source(self, source_id: int, source_code: str) -> None:
+        if not self.sources.set_text(source_id, source_code):
+            raise JasmineEvalException("unknown source id %s" % source_id)
 
-    def has_source(self, source_id: int) -> bool:
-        return source_id in self.sources
+    # reloading a file keeps its source id
+    def add_source(self, filepath: str, source_code: str) -> int:
+        return self.sources.add(filepath, source_code)
 
     def get_max_handle_id(self) -> int:
         if len(self.handles) == 0:
@@ -437,7 +458,7 @@ class Engine:
             is_actives.append(v.is_active)
             descriptions.append(v.description)
             upd_times.append(v.upd_time)
-        timezone = get_timezone()
+        timezone = self.timezone
         return pl.DataFrame(
             [
                 pl.Series("id", ids, dtype=pl.Int64),
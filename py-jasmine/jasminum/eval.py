@@ -2,6 +2,7 @@ import asyncio
 import socket
 import traceback
 from copy import copy
+from functools import lru_cache
 from typing import Callable
 
 import polars as pl
@@ -15,28 +16,37 @@ from .ast import (
     AstCall,
     AstDataFrame,
     AstDict,
+    AstExplain,
     AstFn,
     AstId,
     AstIf,
     AstIndexAssign,
+    AstInsert,
     AstList,
     AstMatrix,
     AstOp,
+    AstParam,
+    AstPivot,
     AstRaise,
     AstReturn,
     AstSeries,
+    AstSetOp,
     AstSkip,
     AstSql,
+    AstSubQuery,
     AstTry,
     AstUnaryOp,
     AstWhile,
+    AstWith,
     JObj,
+    collect_sql,
     downcast_ast_node,
+    explain_sql,
     parse_source_code,
 )
 from .context import Context
 from .engine import Engine
-from .exceptions import JasmineEvalException
+from .exceptions import JasmineError, JasmineEvalException
 from .j import J, JType, date_to_num
 from .j_conn import JConn
 from .j_fn import JFn
@@ -49,8 +59,8 @@ def import_path(path: str, engine: Engine):
 
 
 def eval_src(source_code: str, source_id: int, engine: Engine, ctx: Context) -> J:
-    engine.set_source(source_id, (source_code, ""))
-    nodes = parse_source_code(source_code, source_id)
+    engine.set_source(source_id, source_code)
+    nodes = parse_source_code(source_code, source_id, engine.timezone)
     res = J(None, JType.NULL)
     for node in nodes:
         res = eval_node(node, engine, ctx, False)
@@ -59,11 +69,46 @@ def eval_src(source_code: str, source_id: int, engine: Engine, ctx: Context) ->
     return res
 
 
+class Prepared:
+    # parsed once per timezone of engines, and evaluated with parameters bound to '$1' or
+    # '$name'
+    def __init__(self, source_code: str, source_id: int) -> None:
+        self.source_code = source_code
+        self.source_id = source_id
+        self.nodes_by_timezone = dict()
+
+    def nodes(self, timezone: str) -> list:
+        if timezone not in self.nodes_by_timezone:
+            self.nodes_by_timezone[timezone] = parse_source_code(
+                self.source_code, self.source_id, timezone
+            )
+        return self.nodes_by_timezone[timezone]
+
+    def eval(self, engine: Engine, params: dict | list | None = None) -> J:
+        nodes = self.nodes(engine.timezone)
+        engine.set_source(self.source_id, self.source_code)
+        if isinstance(params, (list, tuple)):
+            params = {str(i + 1): param for i, param in enumerate(params)}
+        ctx = Context(dict())
+        for name, param in (params or {}).items():
+            ctx.set_var("$%s" % name, param if isinstance(param, J) else J(param))
+        res = J(None, JType.NULL)
+        for node in nodes:
+            res = eval_node(node, engine, ctx, False)
+            if res == JType.RETURN:
+                return res.data
+        return res
+
+
+@lru_cache(maxsize=256)
+def prepare(source_code: str, source_id: int = 0) -> Prepared:
+    return Prepared(source_code, source_id)
+
+
 def eval_file(file: str, engine: Engine) -> J:
     with open(file, "r") as f:
         source_code = f.read()
-    source_id = engine.get_max_source_id()
-    engine.set_source(source_id, (file, source_code))
+    source_id = engine.add_source(file, source_code)
     return eval_src(source_code, source_id, engine, Context(dict()))
 
 
@@ -165,6 +210,26 @@ def eval_node(node, engine: Engine, ctx: Context, is_in_fn=False, is_in_sql=Fals
         return J(series)
     elif isinstance(node, AstSql):
         return eval_sql(node, engine, ctx, node.source_id, node.start, is_in_fn)
+    elif isinstance(node, AstWith):
+        return eval_with(node, engine, ctx, is_in_fn)
+    elif isinstance(node, AstExplain):
+        return eval_explain(node, engine, ctx, is_in_fn)
+    elif isinstance(node, AstPivot):
+        return eval_pivot(node, engine, ctx, is_in_fn)
+    elif isinstance(node, AstInsert):
+        return eval_insert(node, engine, ctx, is_in_fn)
+    elif isinstance(node, AstSetOp):
+        return eval_set_op(node, engine, ctx, is_in_fn)
+    elif isinstance(node, AstSubQuery):
+        return eval_sub_query(node, engine, ctx, is_in_fn, is_in_sql)
+    elif isinstance(node, AstParam):
+        if ctx.has_var("$" + node.name):
+            return ctx.get_var("$" + node.name)
+        raise JasmineEvalException(
+            engine.get_trace(
+                node.source_id, node.start, "parameter '$%s' is not bound" % node.name
+            )
+        )
     elif isinstance(node, AstSkip):
         return J(None, JType.MISSING)
     elif isinstance(node, AstReturn):
@@ -634,6 +699,9 @@ def eval_fn(
 # ops: Vec<Ast>,
 # sorts: Vec<Ast>,
 # take: Ast,
+# take_by: Vec<Ast>,
+# skip: Ast,
+# distinct: bool,
 def eval_sql(
     sql: AstSql,
     engine: Engine,
@@ -642,218 +710,526 @@ def eval_sql(
     start: int,
     is_in_fn: bool,
 ):
+    # plan in rust first, fall back to python for partitioned dataframes, files and
+    # expressions the planner doesn't support
     try:
-        j = eval_node(sql.from_df, engine, ctx, is_in_fn)
-        if j.j_type == JType.DATAFRAME:
-            df = j.data.lazy()
-            if len(sql.filters) > 0:
-                if sql.op == "select" or sql.op == "update":
-                    for node in sql.filters:
-                        df = df.filter(
-                            eval_node(node, engine, ctx, is_in_fn, True).to_expr()
-                        )
-                elif sql.op == "delete":
-                    for node in sql.filters:
-                        df = df.filter(
-                            ~eval_node(node, engine, ctx, is_in_fn, True).to_expr()
-                        )
-        elif j.j_type == JType.PARTED:
-            missing_part_err = JasmineEvalException(
-                "dataframe partitioned by %s requires its partitioned unit condition('==', 'in' or 'between') as its first filter clause"
-                % j.j_type.name
-            )
-            # partitioned table
-            if len(sql.filters) > 0:
-                first_filter = downcast_ast_node(sql.filters[0])
-                if isinstance(first_filter, AstBinOp):
-                    op = downcast_ast_node(first_filter.op)
-                    lhs = downcast_ast_node(first_filter.lhs)
-                    rhs = eval_node(first_filter.rhs, engine, ctx, is_in_fn)
-                    if not (isinstance(lhs, AstId) and lhs.name == j.data.get_unit()):
-                        raise missing_part_err
-                    if op.name == "==" and rhs.j_type == JType.DATE:
-                        date_num = rhs.date_num()
-                        partitions = j.data.get_partition_paths(date_num, date_num)
-                        if len(partitions) == 0:
-                            partitions = [j.data.get_latest_path()]
-                            df = pl.scan_parquet(partitions, n_rows=0)
-                        else:
-                            df = pl.scan_parquet(partitions)
-                    elif (
-                        op.name == "between"
-                        and rhs.j_type == JType.SERIES
-                        and rhs.data.dtype == pl.Date
-                        and rhs.data.count() == 2
-                        and rhs.data.null_count() == 0
-                    ):
-                        start_date = rhs.data[0]
-                        end_date = rhs.data[1]
-                        partitions = j.data.get_partition_paths(
-                            date_to_num(start_date), date_to_num(end_date)
+        df = collect_sql(sql, lambda name: resolve_sql_var(name, engine, ctx))
+    except JasmineError as e:
+        raise JasmineEvalException(engine.get_trace(source_id, start, str(e)))
+    if df is not None:
+        return J(df)
+
+    try:
+        return J(plan_sql(sql, engine, ctx, source_id, start, is_in_fn).collect())
+    except Exception as e:
+        # raise e
+        raise JasmineEvalException(engine.get_trace(source_id, start, str(e)))
+
+
+# a predicate pushed down to the parquet scan leaves no filter node on top of the scan
+def is_pushed_down(scan: pl.LazyFrame, expr: pl.Expr) -> bool:
+    plan = scan.filter(expr).explain()
+    return not plan.lstrip().startswith("FILTER")
+
+
+def plan_sql(
+    sql: AstSql,
+    engine: Engine,
+    ctx: Context,
+    source_id: int,
+    start: int,
+    is_in_fn: bool,
+    notes: list[tuple[str, str]] | None = None,
+) -> pl.LazyFrame:
+    j = eval_node(sql.from_df, engine, ctx, is_in_fn)
+    if j.j_type == JType.DATAFRAME:
+        df = j.data.lazy()
+        if len(sql.filters) > 0:
+            if sql.op == "select" or sql.op == "update":
+                for node in sql.filters:
+                    df = df.filter(
+                        eval_node(node, engine, ctx, is_in_fn, True).to_expr()
+                    )
+            elif sql.op == "delete":
+                for node in sql.filters:
+                    df = df.filter(
+                        ~eval_node(node, engine, ctx, is_in_fn, True).to_expr()
+                    )
+    elif j.j_type == JType.PARTED:
+        missing_part_err = JasmineEvalException(
+            "dataframe partitioned by %s requires its partitioned unit condition('==', 'in' or 'between') as its first filter clause"
+            % j.j_type.name
+        )
+        # partitioned table
+        if len(sql.filters) > 0:
+            first_filter = downcast_ast_node(sql.filters[0])
+            if isinstance(first_filter, AstBinOp):
+                op = downcast_ast_node(first_filter.op)
+                lhs = downcast_ast_node(first_filter.lhs)
+                rhs = eval_node(first_filter.rhs, engine, ctx, is_in_fn)
+                if not (isinstance(lhs, AstId) and lhs.name == j.data.get_unit()):
+                    raise missing_part_err
+                if op.name == "==" and rhs.j_type == JType.DATE:
+                    date_num = rhs.date_num()
+                    partitions = j.data.get_partition_paths(date_num, date_num)
+                    if len(partitions) == 0:
+                        partitions = [j.data.get_latest_path()]
+                        df = pl.scan_parquet(partitions, n_rows=0)
+                    else:
+                        df = pl.scan_parquet(partitions)
+                elif (
+                    op.name == "between"
+                    and rhs.j_type == JType.SERIES
+                    and rhs.data.dtype == pl.Date
+                    and rhs.data.count() == 2
+                    and rhs.data.null_count() == 0
+                ):
+                    start_date = rhs.data[0]
+                    end_date = rhs.data[1]
+                    partitions = j.data.get_partition_paths(
+                        date_to_num(start_date), date_to_num(end_date)
+                    )
+                    if len(partitions) == 0:
+                        partitions = [j.data.get_latest_path()]
+                        df = pl.scan_parquet(partitions, n_rows=0)
+                    else:
+                        df = pl.scan_parquet(partitions)
+                elif op.name == "in" and rhs.j_type == JType.DATE:
+                    date_num = date_to_num(rhs.data)
+                    if date_num in j.data.partitions:
+                        df = pl.scan_parquet(
+                            j.data.get_partition_paths_by_date_nums([date_num])
                         )
-                        if len(partitions) == 0:
-                            partitions = [j.data.get_latest_path()]
-                            df = pl.scan_parquet(partitions, n_rows=0)
-                        else:
-                            df = pl.scan_parquet(partitions)
-                    elif op.name == "in" and rhs.j_type == JType.DATE:
-                        date_num = date_to_num(rhs.data)
-                        if date_num in j.data.partitions:
-                            df = pl.scan_parquet(
-                                j.data.get_partition_paths_by_date_nums([date_num])
-                            )
-                        else:
-                            df = pl.scan_parquet([j.data.get_latest_path()], n_rows=0)
-                    elif op.name == "in" and (
-                        rhs.j_type == JType.SERIES and rhs.data.dtype == pl.Date
-                    ):
-                        date_nums = []
-                        for date in rhs.data:
-                            if date:
-                                date_num = date_to_num(date)
-                                if date_num in j.data.partitions:
-                                    date_nums.append(date_num)
-                        partitions = j.data.get_partition_paths_by_date_nums(date_nums)
-                        if len(partitions) > 0:
-                            df = pl.scan_parquet(partitions)
-                        else:
-                            df = pl.scan_parquet([j.data.get_latest_path()], n_rows=0)
                     else:
-                        raise missing_part_err
+                        df = pl.scan_parquet([j.data.get_latest_path()], n_rows=0)
+                elif op.name == "in" and (
+                    rhs.j_type == JType.SERIES and rhs.data.dtype == pl.Date
+                ):
+                    date_nums = []
+                    for date in rhs.data:
+                        if date:
+                            date_num = date_to_num(date)
+                            if date_num in j.data.partitions:
+                                date_nums.append(date_num)
+                    partitions = j.data.get_partition_paths_by_date_nums(date_nums)
+                    if len(partitions) > 0:
+                        df = pl.scan_parquet(partitions)
+                    else:
+                        df = pl.scan_parquet([j.data.get_latest_path()], n_rows=0)
                 else:
                     raise missing_part_err
             else:
                 raise missing_part_err
+        else:
+            raise missing_part_err
 
-            if len(sql.filters) > 1:
-                for node in sql.filters[1:]:
-                    df = df.filter(
-                        eval_node(node, engine, ctx, is_in_fn, True).to_expr()
-                    )
-        elif j.j_type == JType.STRING or j.j_type == JType.STRING:
-            path = j.to_str()
-            if path.endswith(".csv") or path.endswith(".gz"):
-                df = pl.scan_csv(path)
-            elif path.endswith(".json"):
-                df = pl.scan_ndjson(path)
-            elif path.endswith(".parquet"):
-                df = pl.scan_parquet(path)
-            else:
-                raise JasmineEvalException(
-                    "only support file ends with 'csv|gz|json|parquet', got %s" % path
-                )
+        if notes is not None:
+            notes.append(("partition", "%s %s %s" % (lhs.name, op.name, rhs)))
+
+        if len(sql.filters) > 1:
+            scan = df
+            for node in sql.filters[1:]:
+                expr = eval_node(node, engine, ctx, is_in_fn, True).to_expr()
+                df = df.filter(expr)
+                if notes is not None:
+                    kind = "pushed" if is_pushed_down(scan, expr) else "filtered"
+                    notes.append((kind, str(expr)))
+    elif j.j_type == JType.STRING or j.j_type == JType.STRING:
+        path = j.to_str()
+        if path.endswith(".csv") or path.endswith(".gz"):
+            df = pl.scan_csv(path)
+        elif path.endswith(".json"):
+            df = pl.scan_ndjson(path)
+        elif path.endswith(".parquet"):
+            df = pl.scan_parquet(path)
         else:
             raise JasmineEvalException(
-                "'from' requires 'dataframe|partitioned dataframe|csv|parquet|ndjson', got %s"
-                % j.j_type
+                "only support file ends with 'csv|gz|json|parquet', got %s" % path
             )
+    else:
+        raise JasmineEvalException(
+            "'from' requires 'dataframe|partitioned dataframe|csv|parquet|ndjson', got %s"
+            % j.j_type
+        )
+
+    groups = []
+    if len(sql.groups) > 0:
+        if sql.group_type == "by":
+            for node in sql.groups:
+                groups.append(
+                    eval_node(node, engine, ctx, is_in_fn, True).to_expr()
+                )
+        elif len(sql.groups) >= 2:
+            interval = eval_node(sql.groups[0], engine, ctx, is_in_fn, True)
+            if interval.j_type == JType.INT:
+                interval = f"{interval.int()}i"
+            elif interval.j_type == JType.DURATION:
+                interval = f"{interval.data}ns"
+            elif interval.j_type == JType.PERIOD:
+                interval = interval.data
+            else:
+                interval = interval.to_str()
+            index_column = eval_node(
+                sql.groups[1], engine, ctx, is_in_fn, True
+            ).to_expr()
+            group_by = []
+            for node in sql.groups[2:]:
+                group_by.append(
+                    eval_node(node, engine, ctx, is_in_fn, True).to_expr()
+                )
+            groups.append(None)
+        else:
+            raise JasmineEvalException("'%s' requires 'interval' and 'column name'")
 
-        groups = []
-        if len(sql.groups) > 0:
+    ops = []
+    if len(sql.ops) > 0:
+        for node in sql.ops:
+            j = eval_node(node, engine, ctx, is_in_fn, True)
+            ops.append(j.to_expr())
+
+    if len(groups) > 0:
+        if sql.op == "select":
+            # group by
             if sql.group_type == "by":
-                for node in sql.groups:
-                    groups.append(
-                        eval_node(node, engine, ctx, is_in_fn, True).to_expr()
-                    )
-            elif len(sql.groups) >= 2:
-                interval = eval_node(sql.groups[0], engine, ctx, is_in_fn, True)
-                if interval.j_type == JType.INT:
-                    interval = f"{interval.int()}i"
-                elif interval.j_type == JType.DURATION:
-                    interval = f"{interval.data}ns"
-                else:
-                    interval = interval.to_str()
-                index_column = eval_node(
-                    sql.groups[1], engine, ctx, is_in_fn, True
-                ).to_expr()
-                group_by = []
-                for node in sql.groups[2:]:
-                    group_by.append(
-                        eval_node(node, engine, ctx, is_in_fn, True).to_expr()
-                    )
-                groups.append(None)
+                df = df.group_by(groups, maintain_order=True)
+            elif sql.group_type == "dyn":
+                df = df.group_by_dynamic(
+                    index_column, every=interval, group_by=group_by
+                )
             else:
-                raise JasmineEvalException("'%s' requires 'interval' and 'column name'")
-
-        ops = []
-        if len(sql.ops) > 0:
-            for node in sql.ops:
-                j = eval_node(node, engine, ctx, is_in_fn, True)
-                ops.append(j.to_expr())
-
-        if len(groups) > 0:
-            if sql.op == "select":
-                # group by
-                if sql.group_type == "by":
-                    df = df.group_by(groups, maintain_order=True)
-                elif sql.group_type == "dyn":
-                    df = df.group_by_dynamic(
-                        index_column, every=interval, group_by=group_by
-                    )
-                else:
-                    df = df.rolling(index_column, period=interval, group_by=group_by)
-                # agg
-                if len(ops) == 0:
-                    df = df.agg(pl.col("*").last())
-                else:
-                    df = df.agg(ops)
-            elif sql.op == "update":
-                if sql.group_type == "dyn" or sql.group_type == "rolling":
-                    raise JasmineEvalException(
-                        "not support '%s' with 'update'" % sql.group_type
-                    )
-                over_ops = []
-                for op in ops:
-                    over_ops.append(op.over(groups))
-                df.with_columns(over_ops)
+                df = df.rolling(index_column, period=interval, group_by=group_by)
+            # agg
+            if len(ops) == 0:
+                df = df.agg(pl.col("*").last())
             else:
+                df = df.agg(ops)
+        elif sql.op == "update":
+            if sql.group_type == "dyn" or sql.group_type == "rolling":
                 raise JasmineEvalException(
-                    engine.get_trace(
-                        source_id,
-                        start,
-                        "not support 'delete' with '%s'" % sql.group_type,
-                    )
+                    "not support '%s' with 'update'" % sql.group_type
+                )
+            over_ops = []
+            for op in ops:
+                over_ops.append(op.over(groups))
+            df.with_columns(over_ops)
+        else:
+            raise JasmineEvalException(
+                engine.get_trace(
+                    source_id,
+                    start,
+                    "not support 'delete' with '%s'" % sql.group_type,
                 )
-        elif len(ops) > 0:
-            if sql.op == "select":
-                df = df.select(ops)
-            elif sql.op == "update":
-                df = df.with_columns(ops)
+            )
+    elif len(ops) > 0:
+        if sql.op == "select":
+            df = df.select(ops)
+        elif sql.op == "update":
+            df = df.with_columns(ops)
+        else:
+            df = df.drop(ops)
+
+    if sql.distinct:
+        df = df.unique(maintain_order=True)
+
+    sorts = []
+    descendings = []
+    if len(sql.sorts) > 0:
+        for sort in sql.sorts:
+            sort = downcast_ast_node(sort).name
+            if sort.startswith("-"):
+                sorts.append(sort[1:])
+                descendings.append(True)
             else:
-                df = df.drop(ops)
-
-        sorts = []
-        descendings = []
-        if len(sql.sorts) > 0:
-            for sort in sql.sorts:
-                sort = downcast_ast_node(sort).name
-                if sort.startswith("-"):
-                    sorts.append(sort[1:])
-                    descendings.append(True)
-                else:
-                    sorts.append(sort)
-                    descendings.append(False)
-            df = df.sort(sorts, descending=descendings)
+                sorts.append(sort)
+                descendings.append(False)
+        df = df.sort(sorts, descending=descendings)
+
+    skip = eval_node(sql.skip, engine, ctx, is_in_fn, True)
+    if skip.j_type == JType.NULL:
+        skip = 0
+    elif skip.j_type == JType.INT and skip.int() >= 0:
+        skip = skip.int()
+    else:
+        raise JasmineEvalException(
+            engine.get_trace(
+                source_id,
+                start,
+                "requires non-negative 'int' for 'skip', got %s" % skip,
+            )
+        )
+
+    take = eval_node(sql.take, engine, ctx, is_in_fn, True)
+    if take.j_type not in (JType.INT, JType.NULL):
+        raise JasmineEvalException(
+            engine.get_trace(
+                source_id, start, "requires 'int' for 'take', got %s" % take
+            )
+        )
 
-        take = eval_node(sql.take, engine, ctx, is_in_fn, True)
+    if len(sql.take_by) > 0:
+        by = []
+        for node in sql.take_by:
+            by.append(eval_node(node, engine, ctx, is_in_fn, True).to_expr())
+        index = pl.int_range(pl.len())
+        cond = index.over(by) >= skip
         if take.j_type == JType.INT:
             n = take.int()
-            if n > 0:
-                df = df.head(n)
+            if n >= 0:
+                cond = cond & (index.over(by) < skip + n)
             else:
-                df = df.tail(n)
-        elif take.j_type == JType.NULL:
-            pass
+                cond = cond & ((pl.len() - 1 - index).over(by) < -n)
+        return df.filter(cond)
+
+    if skip > 0:
+        df = df.slice(skip)
+    if take.j_type == JType.INT:
+        n = take.int()
+        if n > 0:
+            df = df.head(n)
         else:
+            df = df.tail(n)
+    return df
+
+
+# types of variables the sql planner accepts
+PLANNER_TYPES = {
+    JType.NULL,
+    JType.BOOLEAN,
+    JType.INT,
+    JType.DATE,
+    JType.TIME,
+    JType.DATETIME,
+    JType.TIMESTAMP,
+    JType.DURATION,
+    JType.FLOAT,
+    JType.STRING,
+    JType.CAT,
+    JType.SERIES,
+    JType.MATRIX,
+    JType.LIST,
+    JType.DICT,
+    JType.DATAFRAME,
+    JType.PERIOD,
+    JType.MONTH,
+    JType.QUARTER,
+    JType.WEEK,
+}
+
+
+def resolve_sql_var(name: str, engine: Engine, ctx: Context):
+    # variables for the sql planner as (j_type, data), None for columns
+    if name in engine.builtins:
+        raise JasmineEvalException("'%s' is a builtin function" % name)
+    elif ctx.has_var(name):
+        j = ctx.get_var(name)
+    elif engine.has_var(name):
+        j = engine.get_var(name)
+    else:
+        return None
+    if j.j_type not in PLANNER_TYPES:
+        # planned by python, e.g. partitioned dataframes
+        return (j.j_type.value, None)
+    return (j.j_type.value, j.data)
+
+
+def eval_with(node: AstWith, engine: Engine, ctx: Context, is_in_fn: bool) -> J:
+    with_ctx = bind_ctes(node, engine, ctx, is_in_fn)
+    sql = downcast_ast_node(node.sql)
+    return eval_sql(sql, engine, with_ctx, sql.source_id, sql.start, is_in_fn)
+
+
+def bind_ctes(node: AstWith, engine: Engine, ctx: Context, is_in_fn: bool) -> Context:
+    # bind common table expressions to a copied context, only visible to this query
+    with_ctx = Context(dict(ctx.locals))
+    for name, sql in zip(node.names, node.sqls):
+        sql = downcast_ast_node(sql)
+        with_ctx.set_var(
+            name, eval_sql(sql, engine, with_ctx, sql.source_id, sql.start, is_in_fn)
+        )
+    return with_ctx
+
+
+def eval_explain(node: AstExplain, engine: Engine, ctx: Context, is_in_fn: bool) -> J:
+    try:
+        plans = explain_sql(node.sql, lambda name: resolve_sql_var(name, engine, ctx))
+    except JasmineError as e:
+        raise JasmineEvalException(
+            engine.get_trace(node.source_id, node.start, str(e))
+        )
+    if plans is not None:
+        return J(plans)
+
+    sql = downcast_ast_node(node.sql)
+    if isinstance(sql, AstWith):
+        ctx = bind_ctes(sql, engine, ctx, is_in_fn)
+        sql = downcast_ast_node(sql.sql)
+    notes = []
+    try:
+        df = plan_sql(sql, engine, ctx, sql.source_id, sql.start, is_in_fn, notes)
+        optimized = df.explain()
+        plans = [
+            "logical plan:",
+            df.explain(optimized=False),
+            "",
+            "optimized plan:",
+            optimized,
+        ]
+    except Exception as e:
+        raise JasmineEvalException(
+            engine.get_trace(node.source_id, node.start, str(e))
+        )
+
+    if len(notes) > 0:
+        plans += ["", "partitioned source:"]
+        for kind, predicate in notes:
+            if kind == "partition":
+                plans.append("  %s -> partition pruning" % predicate)
+            elif kind == "pushed":
+                plans.append("  %s -> pushed down to parquet scan" % predicate)
+            else:
+                plans.append("  %s -> filtered after scan" % predicate)
+    return J("\n".join(plans))
+
+
+def eval_pivot(node: AstPivot, engine: Engine, ctx: Context, is_in_fn: bool) -> J:
+    sql = downcast_ast_node(node.sql)
+    df = eval_sql(sql, engine, ctx, sql.source_id, sql.start, is_in_fn).data
+    # sql selects index series first, followed by 'for' and value series for pivot
+    index = df.columns[: node.index]
+    try:
+        if node.kind == "pivot":
+            df = df.pivot(
+                on=node.name, index=index, values=df.columns[node.index + 1 :]
+            )
+        else:
+            df = df.unpivot(
+                on=df.columns[node.index :], index=index, variable_name=node.name
+            )
+    except Exception as e:
+        raise JasmineEvalException(
+            engine.get_trace(node.source_id, node.start, str(e))
+        )
+    return J(df)
+
+
+def eval_insert(node: AstInsert, engine: Engine, ctx: Context, is_in_fn: bool) -> J:
+    in_ctx = is_in_fn and "." not in node.table
+    if in_ctx and ctx.has_var(node.table):
+        table = ctx.get_var(node.table)
+    elif engine.has_var(node.table):
+        table = engine.get_var(node.table)
+    else:
+        raise JasmineEvalException(
+            engine.get_trace(
+                node.source_id, node.start, "'%s' is not defined" % node.table
+            )
+        )
+    rows = eval_node(node.exp, engine, ctx, is_in_fn)
+    if table.j_type != JType.DATAFRAME or rows.j_type != JType.DATAFRAME:
+        raise JasmineEvalException(
+            engine.get_trace(
+                node.source_id,
+                node.start,
+                "'%s' requires 'dataframe' and 'dataframe', got '%s' and '%s'"
+                % (node.kind, table.j_type.name, rows.j_type.name),
+            )
+        )
+    df = table.data
+    rows = rows.data
+    missing = [col for col in rows.columns if col not in df.columns]
+    if len(missing) > 0:
+        raise JasmineEvalException(
+            engine.get_trace(
+                node.source_id,
+                node.start,
+                "columns %s do not exist in '%s'" % (missing, node.table),
+            )
+        )
+    try:
+        rows = rows.cast({col: df.schema[col] for col in rows.columns})
+        if node.kind == "insert":
+            df = pl.concat([df, rows], how="diagonal")
+        else:
+            # nulls of rows overwrite values, columns missing from rows are kept
+            df = df.update(rows, on=node.keys, how="full", include_nulls=True)
+    except Exception as e:
+        raise JasmineEvalException(
+            engine.get_trace(node.source_id, node.start, str(e))
+        )
+    res = J(df)
+    if in_ctx:
+        ctx.set_var(node.table, res)
+    else:
+        engine.set_var(node.table, res)
+    return res
+
+
+def eval_set_op(node: AstSetOp, engine: Engine, ctx: Context, is_in_fn: bool) -> J:
+    lhs = eval_node(node.lhs, engine, ctx, is_in_fn)
+    rhs = eval_node(node.rhs, engine, ctx, is_in_fn)
+    if lhs.j_type != JType.DATAFRAME or rhs.j_type != JType.DATAFRAME:
+        raise JasmineEvalException(
+            engine.get_trace(
+                node.source_id,
+                node.start,
+                "'%s' requires 'dataframe' and 'dataframe', got '%s' and '%s'"
+                % (node.op, lhs.j_type.name, rhs.j_type.name),
+            )
+        )
+    lhs = lhs.data
+    rhs = rhs.data
+    if lhs.width != rhs.width:
+        raise JasmineEvalException(
+            engine.get_trace(
+                node.source_id,
+                node.start,
+                "'%s' requires the same number of columns, got %s and %s"
+                % (node.op, lhs.width, rhs.width),
+            )
+        )
+    # columns are matched by position, and named after the left side
+    rhs = rhs.select(
+        pl.col(name).alias(lhs_name) for name, lhs_name in zip(rhs.columns, lhs.columns)
+    )
+    try:
+        if node.op in ("union all", "union"):
+            df = pl.concat([lhs, rhs])
+        else:
+            how = "semi" if node.op == "intersect" else "anti"
+            df = lhs.join(rhs, on=lhs.columns, how=how, join_nulls=True)
+        if node.op != "union all":
+            df = df.unique(maintain_order=True)
+    except Exception as e:
+        raise JasmineEvalException(
+            engine.get_trace(node.source_id, node.start, str(e))
+        )
+    return J(df)
+
+
+def eval_sub_query(
+    node: AstSubQuery,
+    engine: Engine,
+    ctx: Context,
+    is_in_fn: bool,
+    is_in_sql: bool,
+) -> J:
+    sql = downcast_ast_node(node.sql)
+    df = eval_sql(sql, engine, ctx, sql.source_id, sql.start, is_in_fn).data
+    if node.kind == "scalar":
+        if df.shape != (1, 1):
             raise JasmineEvalException(
                 engine.get_trace(
-                    source_id, start, "requires 'int' for 'take', got %s" % take
+                    node.source_id,
+                    node.start,
+                    "scalar subquery requires 1 row, got %s" % df.height,
                 )
             )
-        return J(df.collect())
-    except Exception as e:
-        # raise e
-        raise JasmineEvalException(engine.get_trace(source_id, start, str(e)))
+        return J(pl.lit(df.item(), dtype=df.dtypes[0]))
+    exp = eval_node(node.exp, engine, ctx, is_in_fn, is_in_sql).to_expr()
+    if node.kind == "in":
+        return J(exp.is_in(df.to_series(0)))
+    else:
+        return J(~exp.is_in(df.to_series(0)))
 
 
 async def handle_ipc(
@@ -4,6 +4,7 @@ from pathlib import Path
 from typing import Literal
 from zoneinfo import ZoneInfo
 
+import numpy as np
 import polars as pl
 
 from .ast import JObj
@@ -35,6 +36,10 @@ class JType(Enum):
     RETURN = 19
     PARTED = 20
     EXPR = 21
+    PERIOD = 22
+    MONTH = 23
+    QUARTER = 24
+    WEEK = 25
 
 
 class JParted:
@@ -88,6 +93,7 @@ class J:
         | float
         | pl.Series
         | pl.DataFrame
+        | np.ndarray
         | JParted
         | pl.Expr
         | list
@@ -145,6 +151,8 @@ class J:
             self.j_type = JType.LIST
         elif isinstance(data, dict):
             self.j_type = JType.DICT
+        elif isinstance(data, np.ndarray) and data.ndim == 2:
+            self.j_type = JType.MATRIX
         else:
             self.j_type = j_type
 
@@ -157,6 +165,7 @@ class J:
                 | JType.INT
                 | JType.FLOAT
                 | JType.SERIES
+                | JType.MATRIX
                 | JType.DATAFRAME
             ):
                 return f"{self.data}"
@@ -166,6 +175,13 @@ class J:
                 return f"'{self.data}'"
             case JType.DATE:
                 return self.data.isoformat()
+            case JType.MONTH:
+                return self.data.strftime("%Y-%mm")
+            case JType.QUARTER:
+                return f"{self.data.year}Q{(self.data.month - 1) // 3 + 1}"
+            case JType.WEEK:
+                year, week, _ = self.data.isocalendar()
+                return f"{year}W{week:02d}"
             case JType.TIME:
                 sss = self.data % 1000000000
                 ss = self.data // 1000000000
@@ -189,7 +205,7 @@ class J:
                 days = HH // 24
                 HH = HH % 24
                 return f"{neg}{days}D{HH:02d}:{mm:02d}:{ss:02d}:{sss:09d}"
-            case JType.PARTED:
+            case JType.PARTED | JType.PERIOD:
                 return str(self.data)
             case JType.LIST:
                 output = "[\n"
@@ -371,6 +387,9 @@ class J:
                 JType.NULL
                 | JType.INT
                 | JType.DATE
+                | JType.MONTH
+                | JType.QUARTER
+                | JType.WEEK
                 | JType.FLOAT
                 | JType.SERIES
                 | JType.STRING
@@ -78,6 +78,13 @@ parser.add_argument(
     help="path to the log file",
 )
 
+parser.add_argument(
+    "--tz",
+    type=str,
+    dest="timezone",
+    help="timezone of datetime and timestamp literals, the timezone of the host by default",
+)
+
 
 async def get_user_input(prompt: str) -> str:
     loop = asyncio.get_event_loop()
@@ -107,7 +114,6 @@ async def handle_user_input(engine: Engine, is_debug=False):
                     break
                 src.append(line)
             src = "\n".join(src)
-            engine.sources[0] = (src, "")
             try:
                 start = time.time()
                 res = eval_src(src, 0, engine, Context(dict()))
@@ -158,8 +164,8 @@ async def timer_task(engine: Engine, sleep_time_seconds: float, is_debug=False):
 
 
 async def async_main():
-    engine = Engine()
     args = parser.parse_args()
+    engine = Engine(args.timezone)
 
     print(
         """\x1b[1;32m\
@@ -0,0 +1,111 @@
+import polars as pl
+
+from . import ops
+from .ast import (
+    matrix_broadcast,
+    matrix_det,
+    matrix_from_dataframe,
+    matrix_from_series,
+    matrix_inverse,
+    matrix_lsq,
+    matrix_mul,
+    matrix_solve,
+    matrix_to_dataframe,
+    matrix_to_series,
+    matrix_transpose,
+)
+from .exceptions import JasmineEvalException
+from .j import J, JType
+
+
+def to_matrix(arg: J) -> J:
+    arg.assert_types([JType.MATRIX, JType.SERIES, JType.DATAFRAME])
+    if arg.j_type == JType.SERIES:
+        return J(matrix_from_series(arg.data))
+    elif arg.j_type == JType.DATAFRAME:
+        return J(matrix_from_dataframe(arg.data))
+    else:
+        return arg
+
+
+def to_df(arg: J) -> J:
+    arg.assert_types([JType.MATRIX])
+    return J(matrix_to_dataframe(arg.data))
+
+
+def to_series(arg: J) -> J:
+    arg.assert_types([JType.MATRIX])
+    return J(matrix_to_series(arg.data))
+
+
+def mul(arg1: J, arg2: J) -> J:
+    return J(matrix_mul(to_matrix(arg1).data, to_matrix(arg2).data))
+
+
+def transpose(arg: J) -> J:
+    return J(matrix_transpose(to_matrix(arg).data))
+
+
+def inv(arg: J) -> J:
+    return J(matrix_inverse(to_matrix(arg).data))
+
+
+def det(arg: J) -> J:
+    return J(matrix_det(to_matrix(arg).data))
+
+
+# a series is a single right-hand side
+def solve(arg1: J, arg2: J) -> J:
+    x = J(matrix_solve(to_matrix(arg1).data, to_matrix(arg2).data))
+    if arg2.j_type == JType.SERIES:
+        return J(matrix_to_series(x.data).alias(arg2.data.name))
+    else:
+        return x
+
+
+# element-wise, a series has a value per column of the matrix
+def broadcast(op: str, arg1: J, arg2: J) -> J:
+    args = []
+    for arg in [arg1, arg2]:
+        if arg.j_type in [JType.MATRIX, JType.SERIES]:
+            args.append(arg.data)
+        elif arg.is_numeric_scalar():
+            args.append(float(arg.data))
+        else:
+            raise JasmineEvalException(
+                "unsupported operand type(s) for '{0}': '{1}' and '{2}'".format(
+                    op, arg1.j_type.name, arg2.j_type.name
+                )
+            )
+    return J(matrix_broadcast(op, args[0], args[1]))
+
+
+# rows with nulls are dropped, a struct of coef, residuals and r2 for expressions, e.g. by sym
+def lsq(y: J, x: J) -> J:
+    return ridge(y, x, J(0.0), J(True))
+
+
+# the penalty applies to every column of x, including a constant column of an intercept, rows
+# with nulls are errors unless 'drop_nulls'
+def ridge(y: J, x: J, penalty: J, drop_nulls: J) -> J:
+    drop_nulls = drop_nulls.to_bool()
+    xs = x.data if x.j_type == JType.LIST else [x]
+    if y.j_type == JType.EXPR or any(j.j_type == JType.EXPR for j in xs):
+        return J(
+            ops.lsq(
+                y.to_expr(),
+                *[j.to_expr() for j in xs],
+                ridge=penalty.float(),
+                drop_nulls=drop_nulls,
+            )
+        )
+    y.assert_types([JType.SERIES])
+    if x.j_type == JType.LIST:
+        columns = {}
+        for i, j in enumerate(xs):
+            if j.j_type == JType.SERIES:
+                columns[f"col{i:02}"] = j.data
+            else:
+                columns[f"col{i:02}"] = pl.repeat(j.float(), len(y.data), eager=True)
+        x = J(pl.DataFrame(columns))
+    return J(matrix_lsq(y.data, to_matrix(x).data, penalty.float(), drop_nulls))
@@ -4,6 +4,7 @@ from typing import Callable
 import numpy as np
 import polars as pl
 
+from . import matrix
 from .constant import PL_DATA_TYPE
 from .exceptions import JasmineEvalException
 from .j import J, JType
@@ -83,6 +84,8 @@ def scalar_op_dict(arg1: J, arg2: J, fn: Callable) -> J:
 def add(arg1: J, arg2: J) -> J:
     if arg1.j_type == JType.EXPR or arg2.j_type == JType.EXPR:
         return J(arg1.to_expr().add(arg2.to_expr()))
+    elif arg1.j_type == JType.MATRIX or arg2.j_type == JType.MATRIX:
+        return matrix.broadcast("+", arg1, arg2)
     elif arg1.j_type == JType.NULL or arg2.j_type == JType.NULL:
         return J(None, JType.NULL)
     elif arg1.j_type.value <= 2 and arg2.j_type.value <= 2:
@@ -158,6 +161,8 @@ def add(arg1: J, arg2: J) -> J:
 def sub(arg1: J, arg2: J) -> J:
     if arg1.j_type == JType.EXPR or arg2.j_type == JType.EXPR:
         return J(arg1.to_expr().sub(arg2.to_expr()))
+    elif arg1.j_type == JType.MATRIX or arg2.j_type == JType.MATRIX:
+        return matrix.broadcast("-", arg1, arg2)
     elif arg1.j_type == JType.NULL or arg2.j_type == JType.NULL:
         return J(None, JType.NULL)
     elif arg1.j_type.value <= 2 and arg2.j_type.value <= 2:
@@ -219,6 +224,8 @@ def sub(arg1: J, arg2: J) -> J:
 def pow(arg1: J, arg2: J) -> J:
     if arg1.j_type == JType.EXPR or arg2.j_type == JType.EXPR:
         return J(arg1.to_expr().pow(arg2.to_expr()))
+    elif arg1.j_type == JType.MATRIX or arg2.j_type == JType.MATRIX:
+        return matrix.broadcast("**", arg1, arg2)
     elif arg1.j_type == JType.NULL or arg2.j_type == JType.NULL:
         return J(None, JType.NULL)
     elif (
@@ -269,6 +276,8 @@ def pow(arg1: J, arg2: J) -> J:
 def mul(arg1: J, arg2: J) -> J:
     if arg1.j_type == JType.EXPR or arg2.j_type == JType.EXPR:
         return J(arg1.to_expr().mul(arg2.to_expr()))
+    elif arg1.j_type == JType.MATRIX or arg2.j_type == JType.MATRIX:
+        return matrix.broadcast("*", arg1, arg2)
     elif arg1.j_type == JType.NULL or arg2.j_type == JType.NULL:
         return J(None, JType.NULL)
     elif arg1.j_type.value <= 2 and arg2.j_type.value <= 2:
@@ -325,6 +334,8 @@ def mul(arg1: J, arg2: J) -> J:
 def true_div(arg1: J, arg2: J) -> J:
     if arg1.j_type == JType.EXPR or arg2.j_type == JType.EXPR:
         return J(arg1.to_expr().truediv(arg2.to_expr()))
+    elif arg1.j_type == JType.MATRIX or arg2.j_type == JType.MATRIX:
+        return matrix.broadcast("/", arg1, arg2)
     elif arg1.is_numeric_scalar() or arg2.is_numeric_scalar():
         return J(arg1.data / arg2.data, JType.FLOAT)
     elif (
@@ -700,6 +711,10 @@ def xor(arg1: J, arg2: J) -> J:
         )
 
 
+# months, quarters and weeks are dates of their first days
+CALENDAR_INTERVALS = {JType.MONTH: "1mo", JType.QUARTER: "1q", JType.WEEK: "1w"}
+
+
 def range(start: J, end: J) -> J:
     if start.j_type == JType.EXPR or end.j_type == JType.EXPR:
         return J(pl.arange(start.to_expr(), end.to_expr()))
@@ -707,6 +722,9 @@ def range(start: J, end: J) -> J:
         return J(pl.arange(start.int(), end.int(), eager=True))
     elif start.j_type == JType.DATE and end.j_type == JType.DATE:
         return J(pl.date_range(start.data, end.data, eager=True))
+    elif start.j_type == end.j_type and start.j_type in CALENDAR_INTERVALS:
+        interval = CALENDAR_INTERVALS[start.j_type]
+        return J(pl.date_range(start.data, end.data, interval, eager=True))
     else:
         raise JasmineEvalException(
             "unsupported operand type(s) for '{0}': '{1}' and '{2}'".format(
@@ -14,3 +14,15 @@ def bar(bar_size: IntoExpr, expr: IntoExpr) -> pl.Expr:
         args=[bar_size, expr],
         is_elementwise=True,
     )
+
+
+def lsq(
+    y: IntoExpr, *xs: IntoExpr, ridge: float = 0.0, drop_nulls: bool = True
+) -> pl.Expr:
+    return register_plugin_function(
+        plugin_path=PLUGIN_PATH,
+        function_name="lsq",
+        args=[y, *xs],
+        kwargs={"ridge": ridge, "drop_nulls": drop_nulls},
+        returns_scalar=True,
+    )
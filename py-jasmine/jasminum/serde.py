@@ -2,14 +2,22 @@ import struct
 from datetime import date, timedelta
 from io import BytesIO
 
+import numpy as np
 import polars as pl
 
-from .exceptions import JasmineEvalException
+from .ast import ipc_deserialize, ipc_serialize
+from .exceptions import JasmineError, JasmineEvalException
 from .j import J, JType
 from .j_fn import JFn
 
 
 def serialize(any: J, compress: bool) -> bytes:
+    msg_bytes = ipc_serialize(any, compress)
+    # functions are only serialized by python
+    return py_serialize(any, compress) if msg_bytes is None else msg_bytes
+
+
+def py_serialize(any: J, compress: bool) -> bytes:
     estimated_size = estimate_size(any)
     match any.j_type:
         case JType.NULL:
@@ -71,6 +79,19 @@ def serialize(any: J, compress: bool) -> bytes:
                 buf.write(b"\x00" * (8 - buf.tell() % 8))
             buf.truncate()
             return buf.getvalue()
+        case JType.MATRIX:
+            data = np.asarray(any.data, dtype="<f8")
+            # column major matrices, e.g. transposed ones, are written without reordering
+            column_major = not data.flags.c_contiguous and data.flags.f_contiguous
+            buf = BytesIO(bytes(estimated_size))
+            buf.write(any.j_type.value.to_bytes(4, "little"))
+            buf.write((16 + 8 * data.size).to_bytes(4, "little"))
+            buf.write(data.shape[0].to_bytes(4, "little"))
+            buf.write(data.shape[1].to_bytes(4, "little"))
+            buf.write(int(column_major).to_bytes(8, "little"))
+            buf.write(data.tobytes(order="F" if column_major else "C"))
+            buf.truncate()
+            return buf.getvalue()
         case JType.LIST:
             buf = BytesIO(bytes(estimated_size))
             buf.write(any.j_type.value.to_bytes(4, "little"))
@@ -78,7 +99,7 @@ def serialize(any: J, compress: bool) -> bytes:
             buf.write(bytes(4))
             buf.write(len(any).to_bytes(8, "little"))
             for item in any.data:
-                buf.write(serialize(item, compress))
+                buf.write(py_serialize(item, compress))
             buf.truncate()
             msg_len = buf.tell() - 8
             buf.seek(4)
@@ -116,7 +137,7 @@ def serialize(any: J, compress: bool) -> bytes:
             # reserve 4 bytes for values length
             buf.write(bytes(8))
             for v in any.data.values():
-                buf.write(serialize(v, compress))
+                buf.write(py_serialize(v, compress))
             full_len = buf.tell()
             buf.seek(4)
             buf.write((full_len - 8).to_bytes(4, "little"))
@@ -159,6 +180,17 @@ def serialize_err(err: str) -> bytes:
 
 
 def deserialize(any: bytearray) -> J:
+    # errors are raised and functions are only deserialized by python
+    if any[0] in (JType.ERR.value, JType.FN.value):
+        return py_deserialize(any)
+    try:
+        return J(ipc_deserialize(bytes(any)))
+    except JasmineError:
+        # functions nested in lists or dicts
+        return py_deserialize(any)
+
+
+def py_deserialize(any: bytearray) -> J:
     j_type = JType(any[0])
     data = any[4:]
     match j_type:
@@ -192,6 +224,11 @@ def deserialize(any: bytearray) -> J:
             msg_len = int.from_bytes(data[:4], "little")
             data = pl.read_ipc(BytesIO(data[4 : 4 + msg_len]))
             return J(data if j_type == JType.DATAFRAME else data.to_series())
+        case JType.MATRIX:
+            rows = int.from_bytes(data[4:8], "little")
+            cols = int.from_bytes(data[8:12], "little")
+            values = np.frombuffer(data[20 : 20 + 8 * rows * cols], dtype="<f8")
+            return J(values.reshape((rows, cols), order="F" if data[12] == 1 else "C"))
         case JType.LIST:
             # byte_len = int.from_bytes(data[:4], "little")
             item_len = int.from_bytes(data[4:8], "little")
@@ -204,7 +241,7 @@ def deserialize(any: bytearray) -> J:
                     sub_byte_len = (
                         int.from_bytes(data[offset + 4 : offset + 8], "little") + 8
                     )
-                item = deserialize(data[offset : offset + sub_byte_len])
+                item = py_deserialize(data[offset : offset + sub_byte_len])
                 # skip padding
                 if sub_byte_len % 8 != 0:
                     sub_byte_len += 8 - sub_byte_len % 8
@@ -238,7 +275,7 @@ def deserialize(any: bytearray) -> J:
                     sub_byte_len = (
                         int.from_bytes(data[offset + 4 : offset + 8], "little") + 8
                     )
-                item = deserialize(data[offset : offset + sub_byte_len])
+                item = py_deserialize(data[offset : offset + sub_byte_len])
                 # skip padding
                 if sub_byte_len % 8 != 0:
                     sub_byte_len += 8 - sub_byte_len % 8
@@ -287,6 +324,9 @@ def estimate_size(any: J) -> int:
             return 8 + len(any.data.encode("utf-8")) + 8
         case JType.SERIES | JType.DATAFRAME:
             return 8 + int(any.data.estimated_size() * 1.1) + 8
+        case JType.MATRIX:
+            # type code(4), byte length(4), rows(4), columns(4), layout(8), values
+            return 24 + 8 * any.data.size
         case JType.LIST:
             # list type code(4), byte length of j list(4), item length of list(8), each item bytes
             return 16 + sum(estimate_size(item) for item in any.data)
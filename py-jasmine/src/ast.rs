@@ -11,6 +11,10 @@ impl Ast {
     pub fn new(ast: AstNode) -> Self {
         Self(ast)
     }
+
+    pub fn node(&self) -> &AstNode {
+        &self.0
+    }
 }
 
 #[derive(Clone, PartialEq)]
@@ -36,6 +40,13 @@ pub enum AstType {
     Series,
     Sql,
     Skip,
+    SubQuery,
+    With,
+    Explain,
+    Pivot,
+    Insert,
+    SetOp,
+    Param,
 }
 
 #[pymethods]
@@ -63,6 +74,13 @@ impl Ast {
             AstNode::Series { .. } => AstType::Series,
             AstNode::Sql { .. } => AstType::Sql,
             AstNode::Skip => AstType::Skip,
+            AstNode::SubQuery { .. } => AstType::SubQuery,
+            AstNode::With { .. } => AstType::With,
+            AstNode::Explain { .. } => AstType::Explain,
+            AstNode::Pivot { .. } => AstType::Pivot,
+            AstNode::Insert { .. } => AstType::Insert,
+            AstNode::SetOp { .. } => AstType::SetOp,
+            AstNode::Param { .. } => AstType::Param,
         };
         ast_type as u8
     }
@@ -406,6 +424,9 @@ impl Ast {
             ops,
             sorts,
             take,
+            take_by,
+            skip,
+            distinct,
             source_id,
             start,
         } = &self.0
@@ -419,6 +440,9 @@ impl Ast {
                 ops: ops.into_iter().map(|n| Ast(n.clone())).collect(),
                 sorts: sorts.into_iter().map(|n| Ast(n.clone())).collect(),
                 take: Ast(*take.clone()),
+                take_by: take_by.into_iter().map(|n| Ast(n.clone())).collect(),
+                skip: Ast(*skip.clone()),
+                distinct: *distinct,
                 source_id: *source_id,
                 start: *start,
             })
@@ -431,6 +455,179 @@ impl Ast {
         }
     }
 
+    pub fn sub_query(&self) -> PyResult<AstSubQuery> {
+        if let AstNode::SubQuery {
+            kind,
+            exp,
+            sql,
+            start,
+            source_id,
+        } = &self.0
+        {
+            Ok(AstSubQuery {
+                kind: kind.to_owned(),
+                exp: Ast(*exp.clone()),
+                sql: Ast(*sql.clone()),
+                start: *start,
+                source_id: *source_id,
+            })
+        } else {
+            Err(PyJasmineErr::new_err(format!(
+                "failed to refer {0} from {1}",
+                "ast sub query",
+                self.get_ast_type()
+            )))
+        }
+    }
+
+    pub fn with_exp(&self) -> PyResult<AstWith> {
+        if let AstNode::With {
+            names,
+            sqls,
+            sql,
+            start,
+            source_id,
+        } = &self.0
+        {
+            Ok(AstWith {
+                names: names.clone(),
+                sqls: sqls.into_iter().map(|n| Ast(n.clone())).collect(),
+                sql: Ast(*sql.clone()),
+                start: *start,
+                source_id: *source_id,
+            })
+        } else {
+            Err(PyJasmineErr::new_err(format!(
+                "failed to refer {0} from {1}",
+                "ast with",
+                self.get_ast_type()
+            )))
+        }
+    }
+
+    pub fn explain(&self) -> PyResult<AstExplain> {
+        if let AstNode::Explain {
+            sql,
+            start,
+            source_id,
+        } = &self.0
+        {
+            Ok(AstExplain {
+                sql: Ast(*sql.clone()),
+                start: *start,
+                source_id: *source_id,
+            })
+        } else {
+            Err(PyJasmineErr::new_err(format!(
+                "failed to refer {0} from {1}",
+                "ast explain",
+                self.get_ast_type()
+            )))
+        }
+    }
+
+    pub fn pivot(&self) -> PyResult<AstPivot> {
+        if let AstNode::Pivot {
+            kind,
+            sql,
+            index,
+            name,
+            start,
+            source_id,
+        } = &self.0
+        {
+            Ok(AstPivot {
+                kind: kind.to_owned(),
+                sql: Ast(*sql.clone()),
+                index: *index,
+                name: name.to_owned(),
+                start: *start,
+                source_id: *source_id,
+            })
+        } else {
+            Err(PyJasmineErr::new_err(format!(
+                "failed to refer {0} from {1}",
+                "ast pivot",
+                self.get_ast_type()
+            )))
+        }
+    }
+
+    pub fn insert(&self) -> PyResult<AstInsert> {
+        if let AstNode::Insert {
+            kind,
+            table,
+            keys,
+            exp,
+            start,
+            source_id,
+        } = &self.0
+        {
+            Ok(AstInsert {
+                kind: kind.to_owned(),
+                table: table.to_owned(),
+                keys: keys.clone(),
+                exp: Ast(*exp.clone()),
+                start: *start,
+                source_id: *source_id,
+            })
+        } else {
+            Err(PyJasmineErr::new_err(format!(
+                "failed to refer {0} from {1}",
+                "ast insert",
+                self.get_ast_type()
+            )))
+        }
+    }
+
+    pub fn set_op(&self) -> PyResult<AstSetOp> {
+        if let AstNode::SetOp {
+            op,
+            lhs,
+            rhs,
+            start,
+            source_id,
+        } = &self.0
+        {
+            Ok(AstSetOp {
+                op: op.to_owned(),
+                lhs: Ast(*lhs.clone()),
+                rhs: Ast(*rhs.clone()),
+                start: *start,
+                source_id: *source_id,
+            })
+        } else {
+            Err(PyJasmineErr::new_err(format!(
+                "failed to refer {0} from {1}",
+                "ast set op",
+                self.get_ast_type()
+            )))
+        }
+    }
+
+    pub fn param(&self) -> PyResult<AstParam> {
+        if let AstNode::Param {
+            name,
+            start,
+            end,
+            source_id,
+        } = &self.0
+        {
+            Ok(AstParam {
+                name: name.to_owned(),
+                start: *start,
+                end: *end,
+                source_id: *source_id,
+            })
+        } else {
+            Err(PyJasmineErr::new_err(format!(
+                "failed to refer {0} from {1}",
+                "ast param",
+                self.get_ast_type()
+            )))
+        }
+    }
+
     pub fn skip(&self) -> PyResult<AstSkip> {
         if let AstNode::Skip = &self.0 {
             Ok(AstSkip {})
@@ -442,6 +639,16 @@ impl Ast {
             )))
         }
     }
+    pub fn to_json(&self) -> PyResult<String> {
+        jasmine::to_json(&self.0).map_err(|e| PyJasmineErr::new_err(e.to_string()))
+    }
+
+    #[staticmethod]
+    pub fn from_json(json: &str) -> PyResult<Ast> {
+        jasmine::from_json(json)
+            .map(Ast)
+            .map_err(|e| PyJasmineErr::new_err(e.to_string()))
+    }
 }
 
 #[pyclass]
@@ -576,6 +783,91 @@ pub struct AstSql {
     ops: Vec<Ast>,
     sorts: Vec<Ast>,
     take: Ast,
+    take_by: Vec<Ast>,
+    skip: Ast,
+    distinct: bool,
+    source_id: usize,
+    start: usize,
+}
+
+impl AstSql {
+    pub fn as_ast_node(&self) -> AstNode {
+        AstNode::Sql {
+            op: self.op.to_owned(),
+            from: Box::new(self.from_df.0.clone()),
+            filters: self.filters.iter().map(|n| n.0.clone()).collect(),
+            group_type: self.group_type.to_owned(),
+            groups: self.groups.iter().map(|n| n.0.clone()).collect(),
+            ops: self.ops.iter().map(|n| n.0.clone()).collect(),
+            sorts: self.sorts.iter().map(|n| n.0.clone()).collect(),
+            take: Box::new(self.take.0.clone()),
+            take_by: self.take_by.iter().map(|n| n.0.clone()).collect(),
+            skip: Box::new(self.skip.0.clone()),
+            distinct: self.distinct,
+            source_id: self.source_id,
+            start: self.start,
+        }
+    }
+}
+
+#[pyclass(get_all)]
+pub struct AstSubQuery {
+    kind: String,
+    exp: Ast,
+    sql: Ast,
+    start: usize,
+    source_id: usize,
+}
+
+#[pyclass(get_all)]
+pub struct AstWith {
+    names: Vec<String>,
+    sqls: Vec<Ast>,
+    sql: Ast,
+    start: usize,
+    source_id: usize,
+}
+
+#[pyclass(get_all)]
+pub struct AstExplain {
+    sql: Ast,
+    start: usize,
+    source_id: usize,
+}
+
+#[pyclass(get_all)]
+pub struct AstPivot {
+    kind: String,
+    sql: Ast,
+    index: usize,
+    name: String,
+    start: usize,
     source_id: usize,
+}
+
+#[pyclass(get_all)]
+pub struct AstInsert {
+    kind: String,
+    table: String,
+    keys: Vec<String>,
+    exp: Ast,
     start: usize,
+    source_id: usize,
+}
+
+#[pyclass(get_all)]
+pub struct AstSetOp {
+    op: String,
+    lhs: Ast,
+    rhs: Ast,
+    start: usize,
+    source_id: usize,
+}
+
+#[pyclass(get_all)]
+pub struct AstParam {
+    name: String,
+    start: usize,
+    end: usize,
+    source_id: usize,
 }
@@ -0,0 +1,31 @@
+use jasmine::ipc;
+use pyo3::{pyfunction, types::PyBytes, Bound, PyAny, PyResult, Python};
+
+use crate::{
+    error::JasmineErr,
+    j::{j_from_py_j, JObj},
+};
+
+// bytes of a python J, none if it contains types only python serializes, e.g. functions
+#[pyfunction]
+pub fn ipc_serialize<'py>(
+    py: Python<'py>,
+    j: &Bound<'py, PyAny>,
+    compress: bool,
+) -> PyResult<Option<Bound<'py, PyBytes>>> {
+    let Some(j) = j_from_py_j(j)? else {
+        return Ok(None);
+    };
+    let bytes = py
+        .allow_threads(|| ipc::serialize(&j, compress))
+        .map_err(JasmineErr::from)?;
+    Ok(Some(PyBytes::new(py, &bytes)))
+}
+
+#[pyfunction]
+pub fn ipc_deserialize(py: Python<'_>, bytes: &[u8]) -> PyResult<JObj> {
+    let j = py
+        .allow_threads(|| ipc::deserialize(bytes))
+        .map_err(JasmineErr::from)?;
+    Ok(JObj::new(j))
+}
@@ -1,9 +1,16 @@
-use chrono::{DateTime, NaiveDate};
+use chrono::{DateTime, Datelike, NaiveDate};
 use chrono_tz::Tz;
 use indexmap::IndexMap;
-use jasmine::{j::J, UNIX_EPOCH_DAY};
-use numpy::ToPyArray;
-use pyo3::{pyclass, pymethods, IntoPyObjectExt, PyObject, PyResult, Python};
+use jasmine::{
+    j::{format_period, J},
+    UNIX_EPOCH_DAY,
+};
+use numpy::{PyReadonlyArray2, ToPyArray};
+use pyo3::{
+    pyclass, pymethods,
+    types::{PyAnyMethods, PyDict, PyDictMethods},
+    Bound, IntoPyObjectExt, PyAny, PyObject, PyRef, PyResult, Python,
+};
 use pyo3_polars::{PyDataFrame, PySeries};
 
 use crate::error::PyJasmineErr;
@@ -56,6 +63,17 @@ impl JObj {
         }
     }
 
+    pub fn to_json(&self) -> PyResult<String> {
+        jasmine::to_json(&self.j).map_err(|e| PyJasmineErr::new_err(e.to_string()))
+    }
+
+    #[staticmethod]
+    pub fn from_json(json: &str) -> PyResult<JObj> {
+        jasmine::from_json(json)
+            .map(JObj::new)
+            .map_err(|e| PyJasmineErr::new_err(e.to_string()))
+    }
+
     pub fn format_temporal(&self) -> PyResult<String> {
         match &self.j {
             J::Datetime { ms, timezone } => {
@@ -113,14 +131,21 @@ impl JObj {
             J::Date(v) => NaiveDate::from_num_days_from_ce_opt(*v + UNIX_EPOCH_DAY)
                 .unwrap()
                 .into_py_any(py),
+            // the first day of months, quarters and weeks
+            J::Month(_) | J::Quarter(_) | J::Week(_) => {
+                NaiveDate::from_num_days_from_ce_opt(self.j.first_day().unwrap() + UNIX_EPOCH_DAY)
+                    .unwrap()
+                    .into_py_any(py)
+            }
             J::Time(v) => v.into_py_any(py),
             J::Datetime { ms, timezone: _ } => ms.into_py_any(py),
             J::Timestamp { ns, timezone: _ } => ns.into_py_any(py),
             J::Duration(v) => v.into_py_any(py),
+            J::Period { months, days, ns } => format_period(*months, *days, *ns).into_py_any(py),
             J::F64(v) => v.into_py_any(py),
             J::String(v) => v.into_py_any(py),
             J::Cat(v) => v.into_py_any(py),
-            J::Null => ().into_py_any(py),
+            J::Null | J::TypedNull(_) => ().into_py_any(py),
             J::Series(series) => PySeries(series.clone()).into_py_any(py),
             J::Matrix(matrix) => matrix.to_pyarray(py).into_py_any(py),
             J::MixedList(l) => {
@@ -146,7 +171,7 @@ impl JObj {
 impl JObj {
     pub fn new(j: J) -> Self {
         let j_type = match j {
-            J::Null => JType::None,
+            J::Null | J::TypedNull(_) => JType::None,
             J::Boolean(_) => JType::Boolean,
             J::I64(_) => JType::I64,
             J::Date(_) => JType::Date,
@@ -154,6 +179,10 @@ impl JObj {
             J::Datetime { .. } => JType::Datetime,
             J::Timestamp { .. } => JType::Timestamp,
             J::Duration(_) => JType::Duration,
+            J::Period { .. } => JType::Period,
+            J::Month(_) => JType::Month,
+            J::Quarter(_) => JType::Quarter,
+            J::Week(_) => JType::Week,
             J::F64(_) => JType::F64,
             J::String(_) => JType::String,
             J::Cat(_) => JType::Cat,
@@ -169,6 +198,10 @@ impl JObj {
             j_type: j_type as u8,
         }
     }
+
+    pub fn j(&self) -> &J {
+        &self.j
+    }
 }
 
 #[derive(Clone, PartialEq)]
@@ -190,4 +223,80 @@ pub enum JType {
     Dict,
     DataFrame,
     Err,
+    // 17 to 21 are only in python
+    Period = 22,
+    Month,
+    Quarter,
+    Week,
+}
+
+// converts data of a python J by its type code, none for types without a rust counterpart,
+// e.g. functions
+pub fn j_from_py(j_type: u8, data: &Bound<'_, PyAny>) -> PyResult<Option<J>> {
+    let j = match j_type {
+        t if t == JType::None as u8 => J::Null,
+        t if t == JType::Boolean as u8 => J::Boolean(data.extract()?),
+        t if t == JType::I64 as u8 => J::I64(data.extract()?),
+        // months, quarters and weeks are dates of their first days in python
+        t if t == JType::Date as u8
+            || t == JType::Month as u8
+            || t == JType::Quarter as u8
+            || t == JType::Week as u8 =>
+        {
+            let date: NaiveDate = data.extract()?;
+            J::Date(date.num_days_from_ce() - UNIX_EPOCH_DAY)
+        }
+        t if t == JType::Time as u8 => J::Time(data.extract()?),
+        t if t == JType::Datetime as u8 || t == JType::Timestamp as u8 => {
+            let j_obj: PyRef<JObj> = data.extract()?;
+            j_obj.j().clone()
+        }
+        t if t == JType::Duration as u8 => J::Duration(data.extract()?),
+        // periods are duration strings in python, e.g. '1mo'
+        t if t == JType::Period as u8 => J::String(data.extract()?),
+        t if t == JType::F64 as u8 => J::F64(data.extract()?),
+        t if t == JType::String as u8 => J::String(data.extract()?),
+        t if t == JType::Cat as u8 => J::Cat(data.extract()?),
+        t if t == JType::Series as u8 => {
+            let series: PySeries = data.extract()?;
+            J::Series(series.0)
+        }
+        t if t == JType::DataFrame as u8 => {
+            let df: PyDataFrame = data.extract()?;
+            J::DataFrame(df.0)
+        }
+        t if t == JType::Matrix as u8 => {
+            let m: PyReadonlyArray2<f64> = data.extract()?;
+            J::Matrix(m.as_array().to_shared())
+        }
+        // items are python J
+        t if t == JType::List as u8 => {
+            let mut list = Vec::new();
+            for item in data.try_iter()? {
+                match j_from_py_j(&item?)? {
+                    Some(j) => list.push(j),
+                    None => return Ok(None),
+                }
+            }
+            J::MixedList(list)
+        }
+        t if t == JType::Dict as u8 => {
+            let mut dict = IndexMap::new();
+            for (k, v) in data.downcast::<PyDict>()?.iter() {
+                match j_from_py_j(&v)? {
+                    Some(j) => dict.insert(k.extract::<String>()?, j),
+                    None => return Ok(None),
+                };
+            }
+            J::Dict(dict)
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(j))
+}
+
+// converts a python J
+pub fn j_from_py_j(j: &Bound<'_, PyAny>) -> PyResult<Option<J>> {
+    let j_type: u8 = j.getattr("j_type")?.getattr("value")?.extract()?;
+    j_from_py(j_type, &j.getattr("data")?)
 }
@@ -1,16 +1,27 @@
 pub mod error;
+pub mod ipc;
 pub mod j;
+pub mod matrix;
 pub mod ops;
 pub mod parse;
+pub mod planner;
+pub mod source_map;
 use error::{PyJasmineErr, PyJasmineParseErr};
+use ipc::{ipc_deserialize, ipc_serialize};
 use j::JObj;
 use jasmine::trace;
-use parse::parse_source_code;
+use matrix::{
+    matrix_broadcast, matrix_det, matrix_from_dataframe, matrix_from_series, matrix_inverse,
+    matrix_lsq, matrix_mul, matrix_solve, matrix_to_dataframe, matrix_to_series, matrix_transpose,
+};
+use parse::{parse_ansi_sql, parse_source_code};
+use planner::{collect_sql, explain_sql};
+use source_map::SourceMap;
 pub mod ast;
 use ast::{
-    Ast, AstAssign, AstBinOp, AstCall, AstDataFrame, AstDict, AstFn, AstId, AstIf, AstIndexAssign,
-    AstList, AstMatrix, AstOp, AstRaise, AstReturn, AstSeries, AstSkip, AstSql, AstTry, AstUnaryOp,
-    AstWhile,
+    Ast, AstAssign, AstBinOp, AstCall, AstDataFrame, AstDict, AstExplain, AstFn, AstId, AstIf,
+    AstIndexAssign, AstInsert, AstList, AstMatrix, AstOp, AstParam, AstPivot, AstRaise, AstReturn,
+    AstSeries, AstSetOp, AstSkip, AstSql, AstSubQuery, AstTry, AstUnaryOp, AstWhile, AstWith,
 };
 use pyo3::prelude::*;
 
@@ -46,9 +57,33 @@ fn jasminum(py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<AstSeries>()?;
     m.add_class::<AstSql>()?;
     m.add_class::<AstSkip>()?;
+    m.add_class::<AstSubQuery>()?;
+    m.add_class::<AstWith>()?;
+    m.add_class::<AstExplain>()?;
+    m.add_class::<AstPivot>()?;
+    m.add_class::<AstInsert>()?;
+    m.add_class::<AstSetOp>()?;
+    m.add_class::<AstParam>()?;
+    m.add_class::<SourceMap>()?;
     m.add_function(wrap_pyfunction!(parse_source_code, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_ansi_sql, m)?)?;
+    m.add_function(wrap_pyfunction!(collect_sql, m)?)?;
+    m.add_function(wrap_pyfunction!(explain_sql, m)?)?;
     m.add_function(wrap_pyfunction!(print_trace, m)?)?;
     m.add_function(wrap_pyfunction!(get_timezone, m)?)?;
+    m.add_function(wrap_pyfunction!(matrix_mul, m)?)?;
+    m.add_function(wrap_pyfunction!(matrix_transpose, m)?)?;
+    m.add_function(wrap_pyfunction!(matrix_inverse, m)?)?;
+    m.add_function(wrap_pyfunction!(matrix_det, m)?)?;
+    m.add_function(wrap_pyfunction!(matrix_solve, m)?)?;
+    m.add_function(wrap_pyfunction!(matrix_broadcast, m)?)?;
+    m.add_function(wrap_pyfunction!(matrix_from_dataframe, m)?)?;
+    m.add_function(wrap_pyfunction!(matrix_to_dataframe, m)?)?;
+    m.add_function(wrap_pyfunction!(matrix_from_series, m)?)?;
+    m.add_function(wrap_pyfunction!(matrix_to_series, m)?)?;
+    m.add_function(wrap_pyfunction!(matrix_lsq, m)?)?;
+    m.add_function(wrap_pyfunction!(ipc_serialize, m)?)?;
+    m.add_function(wrap_pyfunction!(ipc_deserialize, m)?)?;
     Ok(())
 }
 
@@ -0,0 +1,136 @@
+use jasmine::{j::J, matrix};
+use numpy::{ndarray::ArcArray2, PyArray2, PyReadonlyArray2, ToPyArray};
+use pyo3::{
+    pyfunction, types::PyAnyMethods, Bound, IntoPyObjectExt, PyAny, PyObject, PyResult, Python,
+};
+use pyo3_polars::{PyDataFrame, PySeries};
+
+use crate::{
+    error::{JasmineErr, PyJasmineErr},
+    j::JObj,
+};
+
+fn to_matrix(m: PyReadonlyArray2<'_, f64>) -> ArcArray2<f64> {
+    m.as_array().to_shared()
+}
+
+// a matrix, a series or a float
+fn to_j(arg: &Bound<'_, PyAny>) -> PyResult<J> {
+    if let Ok(m) = arg.extract::<PyReadonlyArray2<f64>>() {
+        Ok(J::Matrix(to_matrix(m)))
+    } else if let Ok(s) = arg.extract::<PySeries>() {
+        Ok(J::Series(s.into()))
+    } else if let Ok(f) = arg.extract::<f64>() {
+        Ok(J::F64(f))
+    } else {
+        Err(PyJasmineErr::new_err(format!(
+            "unsupported operand type for matrix: '{}'",
+            arg.get_type()
+        )))
+    }
+}
+
+#[pyfunction]
+pub fn matrix_mul<'py>(
+    py: Python<'py>,
+    lhs: PyReadonlyArray2<'py, f64>,
+    rhs: PyReadonlyArray2<'py, f64>,
+) -> PyResult<Bound<'py, PyArray2<f64>>> {
+    let m = matrix::matmul(&to_matrix(lhs), &to_matrix(rhs)).map_err(JasmineErr::from)?;
+    Ok(m.to_pyarray(py))
+}
+
+#[pyfunction]
+pub fn matrix_transpose<'py>(
+    py: Python<'py>,
+    m: PyReadonlyArray2<'py, f64>,
+) -> Bound<'py, PyArray2<f64>> {
+    matrix::transpose(&to_matrix(m)).to_pyarray(py)
+}
+
+#[pyfunction]
+pub fn matrix_inverse<'py>(
+    py: Python<'py>,
+    m: PyReadonlyArray2<'py, f64>,
+) -> PyResult<Bound<'py, PyArray2<f64>>> {
+    let m = matrix::inverse(&to_matrix(m)).map_err(JasmineErr::from)?;
+    Ok(m.to_pyarray(py))
+}
+
+#[pyfunction]
+pub fn matrix_det(m: PyReadonlyArray2<'_, f64>) -> PyResult<f64> {
+    let det = matrix::det(&to_matrix(m)).map_err(JasmineErr::from)?;
+    Ok(det)
+}
+
+#[pyfunction]
+pub fn matrix_solve<'py>(
+    py: Python<'py>,
+    a: PyReadonlyArray2<'py, f64>,
+    b: PyReadonlyArray2<'py, f64>,
+) -> PyResult<Bound<'py, PyArray2<f64>>> {
+    let m = matrix::solve(&to_matrix(a), &to_matrix(b)).map_err(JasmineErr::from)?;
+    Ok(m.to_pyarray(py))
+}
+
+// op: +, -, *, /, **
+#[pyfunction]
+pub fn matrix_broadcast(
+    py: Python<'_>,
+    op: &str,
+    lhs: &Bound<'_, PyAny>,
+    rhs: &Bound<'_, PyAny>,
+) -> PyResult<PyObject> {
+    match matrix::broadcast(op, &to_j(lhs)?, &to_j(rhs)?).map_err(JasmineErr::from)? {
+        J::Matrix(m) => m.to_pyarray(py).into_py_any(py),
+        j => Err(PyJasmineErr::new_err(format!(
+            "expected a matrix, got '{}'",
+            j.get_type_name()
+        ))),
+    }
+}
+
+#[pyfunction]
+pub fn matrix_from_dataframe(
+    py: Python<'_>,
+    df: PyDataFrame,
+) -> PyResult<Bound<'_, PyArray2<f64>>> {
+    let m = matrix::from_dataframe(&df.into()).map_err(JasmineErr::from)?;
+    Ok(m.to_pyarray(py))
+}
+
+#[pyfunction]
+pub fn matrix_to_dataframe(m: PyReadonlyArray2<'_, f64>) -> PyResult<PyDataFrame> {
+    let df = matrix::to_dataframe(&to_matrix(m)).map_err(JasmineErr::from)?;
+    Ok(PyDataFrame(df))
+}
+
+#[pyfunction]
+pub fn matrix_from_series(py: Python<'_>, s: PySeries) -> PyResult<Bound<'_, PyArray2<f64>>> {
+    let m = matrix::from_series(&s.into()).map_err(JasmineErr::from)?;
+    Ok(m.to_pyarray(py))
+}
+
+#[pyfunction]
+pub fn matrix_to_series(m: PyReadonlyArray2<'_, f64>) -> PyResult<PySeries> {
+    let s = matrix::to_series(&to_matrix(m)).map_err(JasmineErr::from)?;
+    Ok(PySeries(s))
+}
+
+// a dict of coefficients, residuals and r2
+#[pyfunction]
+pub fn matrix_lsq(
+    y: PySeries,
+    x: PyReadonlyArray2<'_, f64>,
+    ridge: f64,
+    drop_nulls: bool,
+) -> PyResult<JObj> {
+    // nulls are nan
+    let y: Vec<f64> = matrix::from_series(&y.into())
+        .map_err(JasmineErr::from)?
+        .iter()
+        .copied()
+        .collect();
+    let out = matrix::lsq(&y, &to_matrix(x), ridge, drop_nulls).map_err(JasmineErr::from)?;
+    Ok(JObj::new(out.into_j()))
+}
@@ -1,5 +1,6 @@
 use polars::prelude::{CompatLevel, Field, PolarsResult, Series};
 use pyo3_polars::derive::polars_expr;
+use serde::Deserialize;
 
 fn bar_output(input_fields: &[Field]) -> PolarsResult<Field> {
     let field = &input_fields[1];
@@ -16,3 +17,21 @@ pub fn bar(inputs: &[Series]) -> PolarsResult<Series> {
         .unwrap()
         .clone())
 }
+
+#[derive(Deserialize)]
+struct LsqKwargs {
+    ridge: f64,
+    drop_nulls: bool,
+}
+
+fn lsq_output(_: &[Field]) -> PolarsResult<Field> {
+    Ok(jasmine_ops::lsq_field())
+}
+
+#[polars_expr(output_type_func = lsq_output)]
+pub fn lsq(inputs: &[Series], kwargs: LsqKwargs) -> PolarsResult<Series> {
+    let columns: Vec<_> = inputs.iter().map(|s| s.clone().into()).collect();
+    Ok(jasmine_ops::lsq(&columns, kwargs.ridge, kwargs.drop_nulls)?
+        .as_materialized_series()
+        .clone())
+}
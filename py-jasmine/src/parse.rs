@@ -1,15 +1,43 @@
-use jasmine::parse;
+use jasmine::{parse_with_options, ParseOptions};
 use pyo3::{pyfunction, PyResult};
 
 use crate::{ast::Ast, error::PyJasmineParseErr};
 
+// literals without a timezone are in 'timezone', or the timezone of the host by default
 #[pyfunction]
-pub fn parse_source_code(source_code: &str, source_id: usize) -> PyResult<Vec<Ast>> {
-    let ast_nodes =
-        parse(source_code, source_id).map_err(|e| PyJasmineParseErr::new_err(e.to_string()))?;
+#[pyo3(signature = (source_code, source_id, timezone=None))]
+pub fn parse_source_code(
+    source_code: &str,
+    source_id: usize,
+    timezone: Option<String>,
+) -> PyResult<Vec<Ast>> {
+    let mut opts = ParseOptions::local(source_id);
+    if let Some(timezone) = timezone {
+        opts.timezone = timezone;
+    }
+    let ast_nodes = parse_with_options(source_code, &opts)
+        .map_err(|e| PyJasmineParseErr::new_err(e.to_string()))?;
 
     Ok(ast_nodes
         .into_iter()
         .map(|n| Ast::new(n))
         .collect::<Vec<_>>())
 }
+
+// timestamp literals are in 'timezone', or the timezone of the host by default
+#[pyfunction]
+#[pyo3(signature = (source_code, source_id, timezone=None))]
+pub fn parse_ansi_sql(
+    source_code: &str,
+    source_id: usize,
+    timezone: Option<String>,
+) -> PyResult<Ast> {
+    let mut opts = ParseOptions::local(source_id);
+    if let Some(timezone) = timezone {
+        opts.timezone = timezone;
+    }
+    let ast_node = jasmine::parse_ansi_sql_with_options(source_code, &opts)
+        .map_err(|e| PyJasmineParseErr::new_err(e.to_string()))?;
+
+    Ok(Ast::new(ast_node))
+}
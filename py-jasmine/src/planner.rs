@@ -0,0 +1,59 @@
+use jasmine::{
+    errors::{JError, JResult},
+    j::J,
+};
+use jasmine_planner::{Planner, Resolver};
+use pyo3::{pyfunction, types::PyAnyMethods, Bound, PyAny, PyResult};
+use pyo3_polars::PyDataFrame;
+
+use crate::{
+    ast::{Ast, AstSql},
+    error::{JasmineErr, PyJasmineErr},
+    j::j_from_py,
+};
+
+// resolves variables by a python callable, which returns (j_type, data) or None for columns
+struct PyResolver<'py>(Bound<'py, PyAny>);
+
+impl Resolver for PyResolver<'_> {
+    fn resolve(&self, name: &str) -> JResult<Option<J>> {
+        let py_err = |e: pyo3::PyErr| JError::Err(e.to_string());
+        let var = self.0.call1((name,)).map_err(py_err)?;
+        if var.is_none() {
+            return Ok(None);
+        }
+        let (j_type, data): (u8, Bound<'_, PyAny>) = var.extract().map_err(py_err)?;
+        match j_from_py(j_type, &data).map_err(py_err)? {
+            Some(j) => Ok(Some(j)),
+            None => Err(JError::UnsupportedErr(format!(
+                "'{}' is not supported by planner",
+                name
+            ))),
+        }
+    }
+}
+
+// none if the planner doesn't support the query, which is evaluated by jasminum instead
+#[pyfunction]
+pub fn collect_sql(sql: &AstSql, resolve: Bound<'_, PyAny>) -> PyResult<Option<PyDataFrame>> {
+    let resolver = PyResolver(resolve);
+    let lf = match Planner::new(&resolver).plan(&sql.as_ast_node()) {
+        Ok(lf) => lf,
+        Err(JError::UnsupportedErr(_)) => return Ok(None),
+        Err(e) => return Err(JasmineErr::from(e).into()),
+    };
+    let df = lf
+        .collect()
+        .map_err(|e| PyJasmineErr::new_err(e.to_string()))?;
+    Ok(Some(PyDataFrame(df)))
+}
+
+#[pyfunction]
+pub fn explain_sql(sql: &Ast, resolve: Bound<'_, PyAny>) -> PyResult<Option<String>> {
+    let resolver = PyResolver(resolve);
+    match Planner::new(&resolver).explain(sql.node()) {
+        Ok(plans) => Ok(Some(plans)),
+        Err(JError::UnsupportedErr(_)) => Ok(None),
+        Err(e) => Err(JasmineErr::from(e).into()),
+    }
+}
@@ -0,0 +1,51 @@
+use pyo3::{pyclass, pymethods};
+
+use crate::ast::Ast;
+
+#[pyclass]
+#[derive(Clone, Default)]
+pub struct SourceMap(jasmine::SourceMap);
+
+#[pymethods]
+impl SourceMap {
+    #[new]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, path: &str, text: &str) -> usize {
+        self.0.add(path, text)
+    }
+
+    pub fn set_text(&mut self, source_id: usize, text: &str) -> bool {
+        self.0.set_text(source_id, text)
+    }
+
+    // (source code, file path), empty for unknown sources
+    pub fn get_source(&self, source_id: usize) -> (String, String) {
+        match self.0.get(source_id) {
+            Some(source) => (source.text.clone(), source.path.clone()),
+            None => ("".to_owned(), "".to_owned()),
+        }
+    }
+
+    pub fn id_of(&self, path: &str) -> Option<usize> {
+        self.0.id_of(path)
+    }
+
+    pub fn line_col(&self, source_id: usize, pos: usize) -> Option<(usize, usize)> {
+        self.0.line_col(source_id, pos)
+    }
+
+    pub fn trace(&self, source_id: usize, pos: usize, msg: &str) -> String {
+        self.0.trace(source_id, pos, msg)
+    }
+
+    pub fn trace_ast(&self, ast: &Ast, msg: &str) -> String {
+        self.0.trace_node(ast.node(), msg)
+    }
+
+    pub fn __len__(&self) -> usize {
+        self.0.len()
+    }
+}
@@ -5,7 +5,8 @@ import pytest
 
 from jasminum.context import Context
 from jasminum.engine import Engine
-from jasminum.eval import eval_src
+from jasminum.eval import eval_file, eval_src
+from jasminum.exceptions import JasmineEvalException
 from jasminum.j import J, JType
 
 
@@ -50,3 +51,38 @@ def test_data_types(src, expect):
         res.data.equals(expect.data)
     else:
         assert res == expect
+
+
+def test_engine_timezone():
+    engine = Engine("Asia/Tokyo")
+    res = eval_src("2024-01-01D09:00:00", 0, engine, Context(dict()))
+    assert res == J.from_nanos(1704067200000000000, "Asia/Tokyo")
+
+
+def test_engine_sources(tmp_path):
+    engine = Engine()
+    eval_src("1 + 1", 0, engine, Context(dict()))
+    assert engine.get_source(0) == ("1 + 1", "")
+
+    path = tmp_path.joinpath("a.jsm")
+    path.write_text("x = 1")
+    assert eval_file(str(path), engine) == J(1)
+    assert engine.get_source(1) == ("x = 1", str(path))
+    # reloading a file keeps its source id
+    path.write_text("x = 2")
+    eval_file(str(path), engine)
+    assert engine.get_source(1) == ("x = 2", str(path))
+    assert engine.get_source(2) == ("", "")
+
+    with pytest.raises(JasmineEvalException, match="unknown source id 2"):
+        engine.set_source(2, "1")
+
+
+def test_ridge_drop_nulls():
+    engine = Engine()
+    src = "ridge(0n 3.0 5.0 7.0, [1, 0.0 1.0 2.0 3.0], 0.0, %s)"
+    res = eval_src(src % "true", 0, engine, Context(dict()))
+    assert res.j_type == JType.DICT
+    assert res.data["coef"].data.round(6).to_list() == [1.0, 2.0]
+    with pytest.raises(Exception, match="null at row 0"):
+        eval_src(src % "false", 0, engine, Context(dict()))
@@ -1,9 +1,11 @@
+import polars as pl
 import pytest
 
 from jasminum.context import Context
 from jasminum.engine import Engine
-from jasminum.eval import eval_src
-from jasminum.j import JType
+from jasminum.eval import eval_src, prepare
+from jasminum.exceptions import JasmineEvalException
+from jasminum.j import J, JType
 
 
 @pytest.fixture
@@ -41,6 +43,16 @@ def prepare_engine() -> Engine:
             "select sym, ask_size, total_ask_size=sum(ask_size) ~over sym from quote",
             (15, 3),
         ),
+        ("pivot sum qty by time for sym from trade", (9, 4)),
+        ("unpivot qty, price by time, sym from trade", (18, 4)),
+        ("select distinct sym from trade", (3, 1)),
+        ("select from trade take 3 skip 7", (2, 4)),
+        ("select from trade take 2 by sym", (6, 4)),
+        ("select from quote take -1 by sym skip 1", (3, 6)),
+        ("select sym from trade union all select sym from quote", (24, 1)),
+        ("select sym from trade union select sym from quote", (3, 1)),
+        ("select time from trade intersect select time from quote", (5, 1)),
+        ("select time from quote except select time from trade", (10, 1)),
     ],
 )
 def test_sql(src, expect, prepare_engine):
@@ -48,3 +60,87 @@ def test_sql(src, expect, prepare_engine):
     res = eval_src(src, 0, engine, Context(dict()))
     assert res.j_type == JType.DATAFRAME
     assert res.data.shape == expect
+
+
+def fail_plan_sql(*args, **kwargs):
+    raise AssertionError("expected to be planned in rust")
+
+
+def test_sql_planner_error(prepare_engine, monkeypatch):
+    engine = prepare_engine
+    # planner errors are not re-run by python
+    monkeypatch.setattr("jasminum.eval.plan_sql", fail_plan_sql)
+    with pytest.raises(JasmineEvalException, match="missing_col"):
+        eval_src("select missing_col from trade", 0, engine, Context(dict()))
+
+
+def test_sql_period_var(prepare_engine, monkeypatch):
+    engine = prepare_engine
+    monkeypatch.setattr("jasminum.eval.plan_sql", fail_plan_sql)
+    res = eval_src(
+        "p = 1d; select count i dyn p, time from trade", 0, engine, Context(dict())
+    )
+    assert res.data.shape == (1, 2)
+
+
+def test_sql_month_var(prepare_engine, monkeypatch):
+    engine = prepare_engine
+    monkeypatch.setattr("jasminum.eval.plan_sql", fail_plan_sql)
+    src = """
+    days = df[date=2024-01-01 2024-01-15 2024-02-01 2024-03-10, v=1 2 3 4];
+    m = 2024-02m;
+    select v from days where date >= m
+    """
+    res = eval_src(src, 0, engine, Context(dict()))
+    assert res.data["v"].to_list() == [3, 4]
+
+
+def test_insert(prepare_engine):
+    engine = prepare_engine
+    res = eval_src("insert into trade df[sym=`d, qty=1000]", 0, engine, Context(dict()))
+    assert res.data.shape == (10, 4)
+    assert engine.get_var("trade").data.shape == (10, 4)
+
+    res = eval_src(
+        "upsert into trade on sym df[sym=`d`e, qty=0 0]", 0, engine, Context(dict())
+    )
+    assert res.data.shape == (11, 4)
+    assert res.data["qty"].to_list()[-2:] == [0, 0]
+
+    res = eval_src(
+        "upsert into trade on sym df[sym=`d`e, qty=0n 5]", 0, engine, Context(dict())
+    )
+    assert res.data.shape == (11, 4)
+    assert res.data["qty"].to_list()[-2:] == [None, 5]
+    assert res.data["price"].to_list()[-2:] == [None, None]
+
+
+def test_prepare(prepare_engine):
+    engine = prepare_engine
+    prepared = prepare("select from trade where sym == $sym, qty > $1")
+    assert prepare("select from trade where sym == $sym, qty > $1") is prepared
+    res = prepared.eval(engine, {"sym": J("a", JType.CAT), "1": 100})
+    assert res.data.shape == (2, 4)
+    res = prepared.eval(engine, {"sym": J("b", JType.CAT), "1": 0})
+    assert res.data.shape == (3, 4)
+
+
+def test_explain_partitioned_source(tmp_path):
+    engine = Engine()
+    trade_path = tmp_path.joinpath("trade")
+    trade_path.mkdir()
+    pl.DataFrame({"sym": ["a", "b", "a"], "qty": [100, 200, 300]}).write_parquet(
+        trade_path.joinpath("20241218_0000")
+    )
+    engine.load_partitioned_df(J(str(tmp_path)))
+    res = eval_src(
+        "explain select from trade where date == 2024-12-18, qty > 150",
+        0,
+        engine,
+        Context(dict()),
+    )
+    lines = res.data.splitlines()
+    partitioned = lines[lines.index("partitioned source:") + 1 :]
+    assert len(partitioned) == 2
+    assert partitioned[0].endswith("-> partition pruning")
+    assert partitioned[1].endswith("-> pushed down to parquet scan")